@@ -27,7 +27,7 @@ pub enum ContentError {
     MissingSquad,
     MissingController,
     MissingWeapons,
-    MissingReference,
+    MissingTurrets,
     MissingColliderString,
     ColliderDecodeError,
 }
@@ -43,7 +43,7 @@ impl Display for ContentError {
             Self::MissingSquad => { "missing 'prefab_squad'" },
             Self::MissingController => { "missing 'controller'" },
             Self::MissingWeapons => { "missing 'weapon_set'" },
-            Self::MissingReference => { "missing 'reference'" },
+            Self::MissingTurrets => { "missing 'turrets'" },
             Self::MissingColliderString => { "missing 'collider_string'" },
             Self::ColliderDecodeError => { "malformed 'collider_string'" }
         };
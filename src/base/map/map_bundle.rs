@@ -0,0 +1,73 @@
+use std::fmt::Display;
+use bevy::prelude::*;
+use avian3d::prelude::Collider;
+use serde::{Serialize, Deserialize};
+use superstruct::superstruct;
+use crate::*;
+
+///Which map definition something belongs to, named after its file in `maps/`. `maps/developer.ron` is `developer`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+#[derive(Component)]
+#[serde(transparent)]
+pub struct MapId(pub String);
+
+impl From<&str> for MapId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+impl Display for MapId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[superstruct{
+    variants(Bundle, Prefab, Serde),
+    variant_attributes(derive(Debug, Clone)),
+    specific_variant_attributes(
+        Bundle(derive(Bundle)),
+        Serde(derive(Default, Serialize, Deserialize, Resource)),
+    ),
+}]
+pub struct Map {
+    #[superstruct(only(Prefab))]            pub bounds: MapBounds,
+    ///Maps without one get colliders built from their scene's meshes once it spawns.
+    #[superstruct(only(Prefab))]            pub collider: Option<Collider>,
+    #[superstruct(only(Bundle, Serde))]     pub id: MapId,
+    #[superstruct(only(Bundle))]            pub snowflake: Snowflake,
+    #[superstruct(only(Bundle))]            pub visibility: Visibility,
+    #[superstruct(only(Bundle))]            pub transform: Transform,
+}
+
+impl TryFrom<&MapAsset> for MapPrefab {
+    type Error = ContentError;
+    fn try_from(map: &MapAsset) -> Result<Self, ContentError> {
+        let Some(bounds) = map.bounds else { return Err(ContentError::MissingBounds); };
+        let collider = match map.collider_string.clone() {
+            Some(collider_string) => {
+                let Some((vertices, indices)) = decode(collider_string) else { return Err(ContentError::ColliderDecodeError); };
+                Some(Collider::trimesh(vertices, indices))
+            },
+            None => None,
+        };
+
+        Ok(Self {
+            bounds,
+            collider,
+        })
+    }
+}
+
+impl From<(MapSerde, &MapPrefab)> for MapBundle {
+    fn from((save, _prefab): (MapSerde, &MapPrefab)) -> Self {
+        Self {
+            id: save.id,
+            snowflake: Snowflake::new(),
+            visibility: Visibility::default(),
+            transform: Transform::default(),
+        }
+    }
+}
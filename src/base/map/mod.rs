@@ -1,32 +1,16 @@
-pub mod developer;
+pub mod map_bundle;
+pub mod terrain;
 
-pub use developer::*;
+pub use map_bundle::*;
+pub use terrain::*;
 
-use std::fmt::Display;
-use bevy::{prelude::*, reflect::TypePath, asset::{AssetLoader, io::Reader}};
+use bevy::{platform::collections::HashMap, prelude::*, reflect::TypePath, asset::{AssetLoader, io::Reader}};
 use serde::{Serialize, Deserialize};
-use bevy_asset_loader::prelude::AssetCollection;
+use bevy_asset_loader::{prelude::AssetCollection, mapped::AssetFileStem};
+use avian3d::prelude::{Collider, ColliderConstructor, ColliderConstructorHierarchy};
 use pathing::DS2Map;
 use crate::*;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-#[derive(Serialize, Deserialize)]
-#[derive(Component)]
-
-pub enum MapType {
-    Developer,
-    // Direction,
-    // Sandbox,
-}
-
-impl Display for MapType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            MapType::Developer => write!(f, "Developer")
-        }
-    }
-}
-
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Resource)]
 pub struct MapBounds(pub Vec2);
 
@@ -48,27 +32,36 @@ pub struct MapLoadEvent {
     pub map_serde: MapSerde,
 }
 
-#[derive(Debug, Clone, Copy)]
-#[derive(Serialize, Deserialize)]
-#[derive(Resource)]
-pub enum MapSerde {
-    Developer(DeveloperSerde),
-}
-
-impl Default for MapSerde {
-    fn default() -> Self {
-        Self::Developer(default())
-    }
-}
-
+///A map definition. Every file in `maps/` is one map, referenced by its [`MapId`].
 #[derive(Debug, Clone)]
 #[derive(Serialize, Deserialize)]
 #[derive(Asset, TypePath)]
 pub struct MapAsset {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    ///Path to the map's scene, such as `models/developer.glb#Scene0`.
+    pub scene: String,
+    #[serde(skip)]
+    #[dependency]
+    pub scene_handle: Handle<Scene>,
     pub bounds: Option<MapBounds>,
+    ///Stored in the file by the `bake` subcommand. Maps without one path as if they were open, flat ground.
+    #[serde(default)]
+    pub terrain: Option<TerrainGrid>,
+    ///Without one, colliders come from the scene's meshes and no terrain grid can be baked.
     pub collider_string: Option<String>,
 }
 
+impl MapAsset {
+    pub fn bake_terrain(&mut self) {
+        if self.terrain.is_some() { return; }
+        let Some(bounds) = self.bounds else { return; };
+        let Some((vertices, indices)) = self.collider_string.clone().and_then(decode) else { return; };
+        self.terrain = Some(TerrainGrid::bake(&Collider::trimesh(vertices, indices), &bounds, &GridSpace::new()));
+    }
+}
+
 #[derive(Default)]
 pub struct MapAssetLoader;
 
@@ -81,11 +74,15 @@ impl AssetLoader for MapAssetLoader {
         &self,
         reader: &mut dyn Reader,
         _settings: &(),
-        _load_context: &mut bevy::asset::LoadContext<'_>,
+        load_context: &mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
-        let asset = ron::de::from_bytes::<MapAsset>(&bytes).or_else(|_| bincode::deserialize(&bytes).map(|map_asset| map_asset))?;
+        let mut asset = ron::de::from_bytes::<MapAsset>(&bytes).or_else(|_| bincode::deserialize(&bytes).map(|map_asset| map_asset))?;
+        if asset.terrain.is_none() {
+            warn!("{} has no baked terrain, run the bake subcommand on it", load_context.path().display());
+        }
+        asset.scene_handle = load_context.load(asset.scene.clone());
         Ok(asset)
     }
 
@@ -98,21 +95,18 @@ impl AssetLoader for MapAssetLoader {
 #[derive(Resource)]
 #[derive(AssetCollection)]
 pub struct MapAssets {
-    #[asset(path = "maps/developer.ron")]
-    pub developer: Handle<MapAsset>
+    ///Every map definition in `maps/`, keyed by file stem.
+    #[asset(path = "maps", collection(typed, mapped))]
+    pub maps: HashMap<AssetFileStem, Handle<MapAsset>>,
 }
 
 impl MapAssets {
-    pub fn from_map_type(&self, map_type: MapType) -> &Handle<MapAsset> {
-        match map_type {
-            MapType::Developer => &self.developer
-        }
+    pub fn get(&self, id: &MapId) -> Option<&Handle<MapAsset>> {
+        self.maps.get(id.0.as_str())
     }
 
-    pub fn from_serde_map(&self, serde_map: &MapSerde) -> &Handle<MapAsset> {
-        match serde_map {
-            MapSerde::Developer(_) => &self.developer
-        }
+    pub fn ids(&self) -> impl Iterator<Item = MapId> + '_ {
+        self.maps.keys().map(|stem| MapId::from(stem.as_ref()))
     }
 }
 
@@ -129,31 +123,39 @@ impl MapPlugin {
         for event in load_events.read() {
             commands.insert_resource(event.map_serde.clone());
 
-            let map_handle: Handle<MapAsset> = map_assets.from_serde_map(&event.map_serde).clone();
-            let Some(map_asset) = maps.get(&map_handle) else { return; };
-            let Some(bounds) = map_asset.bounds else { return; };
-            commands.insert_resource(bounds);
-            commands.insert_resource(GridMap(DS2Map::new()));
+            let Some(map_asset) = map_assets.get(&event.map_serde.id).and_then(|handle| maps.get(handle)) else {
+                error!("No map named {}", event.map_serde.id);
+                return;
+            };
+            let Ok(prefab) = MapPrefab::try_from(map_asset) else { return; };
+            commands.insert_resource(prefab.bounds);
+            commands.insert_resource(map_asset.terrain.as_ref().map_or_else(|| GridMap(DS2Map::new(), default()), |terrain| terrain.grid_map()));
+            commands.insert_resource(TerrainBlocks(map_asset.terrain.as_ref().map(|terrain| terrain.blocked.iter().copied().collect()).unwrap_or_default()));
             commands.insert_resource(GridSpace::new());
 
-            match event.map_serde {
-                MapSerde::Developer(developer) => { commands.spawn(DeveloperBundle::from((developer, &map_asset.try_into().unwrap()))); }
+            let mut map = commands.spawn(MapBundle::from((event.map_serde.clone(), &prefab)));
+            if let Some(collider) = prefab.collider {
+                map.insert(collider);
             }
             status.map_loaded = true;
         }
     }
 
     pub fn spawn_map(
-        gltf_assets: Res<GltfAssets>,
-        maps: Query<(Entity, &MapType), Added<MapType>>,
+        map_assets: Res<MapAssets>,
+        assets: Res<Assets<MapAsset>>,
+        maps: Query<(Entity, &MapId, Has<Collider>), Added<MapId>>,
         mut commands: Commands,
     ) {
-        maps.iter().for_each(|(entity, map)| {
-            let Some(scene) = gltf_assets.get_map(*map) else { return; };
+        maps.iter().for_each(|(entity, id, has_collider)| {
+            let Some(map_asset) = map_assets.get(id).and_then(|handle| assets.get(handle)) else { return; };
             commands.entity(entity).with_children(|parent| {
-                parent.spawn(
-                    SceneRoot(scene.clone())
+                let mut scene = parent.spawn(
+                    SceneRoot(map_asset.scene_handle.clone())
                 );
+                if !has_collider {
+                    scene.insert(ColliderConstructorHierarchy::new(ColliderConstructor::TrimeshFromMesh));
+                }
             });
         });
     }
@@ -163,7 +165,8 @@ impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_event::<MapLoadEvent>()
-            .add_systems(Update, (Self::load_map.run_if(resource_exists::<MapAssets>), Self::spawn_map.run_if(resource_exists::<GltfAssets>)))
+            .init_resource::<TerrainBlocks>()
+            .add_systems(Update, (Self::load_map.run_if(resource_exists::<MapAssets>), Self::spawn_map.run_if(resource_exists::<MapAssets>)))
         ;
     }
 }
\ No newline at end of file
@@ -0,0 +1,98 @@
+use std::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use avian3d::prelude::Collider;
+use serde::{Serialize, Deserialize};
+use pathing::DS2Map;
+use crate::*;
+
+///Steepest slope, in radians, a cell can have and still be driven over.
+pub const MAX_SLOPE: f32 = 0.6;
+///Largest height difference between neighbouring cells before the edge counts as a cliff.
+pub const MAX_STEP: f32 = 1.5;
+///What the steepest passable ground costs to cross compared to flat ground.
+pub const ROUGH_COST: f32 = 3.0;
+///Costs closer to 1 than this aren't worth storing.
+const COST_EPSILON: f32 = 0.05;
+///How high above the map the height probes start.
+const PROBE_HEIGHT: f32 = 1000.0;
+
+///How much more than flat ground each cell costs to cross. Cells that aren't listed cost 1.
+#[derive(Debug, Default, Clone)]
+pub struct TerrainCosts(pub HashMap<(isize, isize), f32>);
+
+impl TerrainCosts {
+    pub fn cost(&self, cell: (isize, isize)) -> f32 {
+        self.0.get(&cell).copied().unwrap_or(1.0)
+    }
+}
+
+///The cells the map's baked terrain blocks. Kept apart from the [`GridMap`] so clearing an object's footprint doesn't open
+///up ground that was never passable.
+#[derive(Debug, Default, Clone)]
+#[derive(Resource)]
+pub struct TerrainBlocks(pub HashSet<(isize, isize)>);
+
+impl TerrainBlocks {
+    ///The cells in `blocks` that the terrain doesn't block by itself, and so can be unblocked again.
+    pub fn removable(&self, blocks: Vec<(isize, isize)>) -> Vec<(isize, isize)> {
+        blocks.into_iter().filter(|block| !self.0.contains(block)).collect()
+    }
+}
+
+///The pathing grid baked from a map's geometry.
+#[derive(Debug, Default, Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct TerrainGrid {
+    pub blocked: Vec<(isize, isize)>,
+    pub costs: Vec<((isize, isize), f32)>,
+}
+
+impl TerrainGrid {
+    ///Probes the height and slope at every cell within the bounds. Cells with no ground, too much slope or a cliff to a
+    ///neighbour are blocked; the rest get more expensive the steeper and bumpier they are.
+    pub fn bake(collider: &Collider, bounds: &MapBounds, space: &GridSpace) -> Self {
+        let (low, high) = (space.position_to_index(-bounds.0 / 2.0), space.position_to_index(bounds.0 / 2.0));
+        let probe = |cell: (isize, isize)| {
+            let position = space.index_to_position(cell);
+            collider.cast_ray(Vec3::ZERO, Quat::IDENTITY, Vec3::new(position.x, PROBE_HEIGHT, position.y), Vec3::NEG_Y, PROBE_HEIGHT * 2.0, true)
+                .map(|(distance, normal)| (PROBE_HEIGHT - distance, normal.normalize_or_zero().y.abs().clamp(0.0, 1.0).acos()))
+        };
+        let mut samples = HashMap::new();
+        for x in low.0..=high.0 {
+            for y in low.1..=high.1 {
+                samples.insert((x, y), probe((x, y)));
+            }
+        }
+
+        let mut terrain = Self::default();
+        for (cell, sample) in samples.iter() {
+            let Some((height, slope)) = sample else {
+                terrain.blocked.push(*cell);
+                continue;
+            };
+            let step = [(1, 0), (-1, 0), (0, 1), (0, -1)].iter()
+                .filter_map(|(x, y)| samples.get(&(cell.0 + x, cell.1 + y)).copied().flatten())
+                .map(|(neighbour, _)| (neighbour - height).abs())
+                .fold(0.0, f32::max);
+            if *slope > MAX_SLOPE || step > MAX_STEP {
+                terrain.blocked.push(*cell);
+                continue;
+            }
+            let roughness = (slope / MAX_SLOPE).max(step / MAX_STEP);
+            let cost = 1.0 + roughness * (ROUGH_COST - 1.0);
+            if cost > 1.0 + COST_EPSILON {
+                terrain.costs.push((*cell, cost));
+            }
+        }
+        terrain
+    }
+
+    pub fn grid_map(&self) -> GridMap {
+        let mut map = DS2Map::new();
+        if !self.blocked.is_empty() {
+            map.add_objects(self.blocked.clone());
+            map.precompute();
+        }
+        GridMap(map, TerrainCosts(self.costs.iter().copied().collect()))
+    }
+}
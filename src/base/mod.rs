@@ -17,8 +17,6 @@ pub static BASE_LABEL: &'static str = "base";
 
 #[derive(Debug, Default, Clone, Resource, AssetCollection)]
 pub struct GltfAssets {
-    #[asset(path = "models/developer.glb#Scene0")]
-    pub developer: Handle<Scene>,
     #[asset(path = "models/crane_yard.glb#Scene0")]
     pub crane_yard: Handle<Scene>,
     #[asset(path = "models/resource_node.glb#Scene0")]
@@ -43,12 +41,6 @@ pub struct GltfAssets {
 }
 
 impl GltfAssets {
-    pub fn get_map(&self, map_type: MapType) -> Option<&Handle<Scene>>  {
-        match map_type {
-            MapType::Developer => Some(&self.developer),
-            // _ => None,
-        }
-    }
     pub fn get_object(&self, object_type: ObjectType) -> Option<&Handle<Scene>> {
         match object_type {
             ObjectType::CraneYard => Some(&self.crane_yard),
@@ -57,6 +49,8 @@ impl GltfAssets {
             ObjectType::ResourcePlatformClaimed => Some(&self.resource_platform_claimed),
             ObjectType::Barracks => Some(&self.barracks),
             ObjectType::Factory => Some(&self.factory),
+            //TODO: Give defense turrets their own model. Until then they borrow the tank base, and their collider is the tank's.
+            ObjectType::DefenseTurret => Some(&self.tank_base),
             ObjectType::Armadillo => Some(&self.armadillo),
             ObjectType::Marine => Some(&self.marine),
             ObjectType::TankBase => Some(&self.tank_base),
@@ -29,8 +29,14 @@ impl From<Armadillo> for ObjectType {
 #[derive(Debug, Clone)]
 pub struct Armadillo {
     #[superstruct(only(Prefab, Bundle))]    pub health: Health,
+    #[superstruct(only(Prefab, Bundle))]    pub death: Death,
+    #[superstruct(only(Bundle))]            pub status_effects: StatusEffects,
     #[superstruct(only(Prefab, Bundle))]    pub controller: Navigator,
     #[superstruct(only(Prefab, Bundle))]    pub weapon_set: WeaponSet,
+    #[superstruct(only(Prefab, Bundle))]    pub stance: Stance,
+    #[superstruct(only(Bundle))]            pub orders: Orders,
+    #[superstruct(only(Prefab, Bundle))]    pub garrison: Garrison,
+    #[superstruct(only(Prefab, Bundle))]    pub veterancy: Veterancy,
     #[superstruct(only(Prefab, Bundle))]    pub collider: Collider,
     #[superstruct(only(Bundle))]            pub tank_marker: Armadillo,
     #[superstruct(only(Bundle))]            pub object_type: ObjectType,
@@ -44,9 +50,14 @@ pub struct Armadillo {
     #[superstruct(only(Bundle, Disk))]      pub transform: Transform,
     #[superstruct(only(Disk))]              pub disk_snowflake: Option<Snowflake>,
     #[superstruct(only(Disk))]              pub disk_health: Option<Health>,
+    #[superstruct(only(Disk))]              pub disk_status_effects: Option<StatusEffects>,
     #[superstruct(only(Disk))]              pub disk_path_finder: Option<PathFinder>,
     #[superstruct(only(Disk))]              pub disk_controller: Option<Navigator>,
     #[superstruct(only(Disk))]              pub disk_weapon_set: Option<WeaponSet>,
+    #[superstruct(only(Disk))]              pub disk_stance: Option<Stance>,
+    #[superstruct(only(Disk))]              pub disk_orders: Option<Orders>,
+    #[superstruct(only(Disk))]              pub disk_garrison: Option<Garrison>,
+    #[superstruct(only(Disk))]              pub disk_experience: Option<f32>,
     #[superstruct(only(Disk))]              pub disk_velocity: Option<LinearVelocity>,
 }
 
@@ -63,8 +74,12 @@ impl TryFrom<&ObjectAsset> for ArmadilloPrefab {
 
         Ok(Self {
             health,
+            death: prefab.death.unwrap_or_default(),
             controller,
             weapon_set,
+            stance: prefab.stance.unwrap_or_default(),
+            garrison: prefab.garrison.map(Garrison::from).unwrap_or_default(),
+            veterancy: Veterancy::new(prefab.veterancy.clone().unwrap_or_default(), health.max_health()),
             collider,
         })
     }
@@ -81,9 +96,14 @@ impl ArmadilloBundle {
     pub fn with_disk_data(mut self, disk_data: Option<ObjectDiskData>) -> Self {
         let Some(disk_data) = disk_data else { return self; };
         if let Some(health) = disk_data.health { self.health = health; }
+        if let Some(status_effects) = disk_data.status_effects { self.status_effects = status_effects; }
         if let Some(path_finder) = disk_data.path_finder { self.path_finder = path_finder; }
         if let Some(controller) = disk_data.navigator { self.controller = controller; }
         if let Some(weapon_set) = disk_data.weapon_set { self.weapon_set = weapon_set; }
+        if let Some(stance) = disk_data.stance { self.stance = stance; }
+        if let Some(orders) = disk_data.orders { self.orders = orders; }
+        if let Some(garrison) = disk_data.garrison { self.garrison = garrison; }
+        if let Some(experience) = disk_data.experience { self.veterancy.set_experience(experience); }
         if let Some(velocity) = disk_data.velocity { self.velocity = velocity; }
         self
     }
@@ -96,9 +116,15 @@ impl From<ArmadilloPrefab> for ArmadilloBundle {
             object_type: Armadillo::default().into(),
             snowflake: Snowflake::new(),
             health: prefab.health,
+            death: prefab.death,
+            status_effects: StatusEffects::default(),
             path_finder: PathFinder::default(),
             controller: prefab.controller,
             weapon_set: prefab.weapon_set,
+            stance: prefab.stance,
+            orders: Orders::default(),
+            garrison: prefab.garrison.clone(),
+            veterancy: prefab.veterancy.clone(),
             team_player: TeamPlayer::default(),
             selectable: Selectable::multiselect(),
             velocity: LinearVelocity::default(),
@@ -117,9 +143,15 @@ impl From<(ArmadilloDisk, &ArmadilloPrefab)> for ArmadilloBundle {
             object_type: Armadillo::default().into(),
             snowflake: save.disk_snowflake.unwrap_or(Snowflake::new()),
             health: save.disk_health.unwrap_or(prefab.health),
+            death: prefab.death,
+            status_effects: save.disk_status_effects.unwrap_or_default(),
             path_finder: save.disk_path_finder.unwrap_or_default(),
             controller: save.disk_controller.unwrap_or(prefab.controller),
             weapon_set: save.disk_weapon_set.unwrap_or(prefab.weapon_set.clone()),
+            stance: save.disk_stance.unwrap_or(prefab.stance),
+            orders: save.disk_orders.unwrap_or_default(),
+            garrison: save.disk_garrison.unwrap_or(prefab.garrison.clone()),
+            veterancy: prefab.veterancy.clone().with_experience(save.disk_experience),
             team_player: save.team_player,
             velocity: save.disk_velocity.unwrap_or(LinearVelocity::default()),
             rigid_body: RigidBody::Kinematic,
@@ -136,12 +168,17 @@ impl<'a> From<ArmadilloDiskQuery<'a>> for ArmadilloDisk {
         Self {
             disk_snowflake: Some(*object.0),
             disk_health: object.1.slim(),
-            disk_path_finder: object.2.slim(),
-            disk_controller: object.3.slim(),
-            disk_weapon_set: object.4.slim(),
-            disk_velocity: object.5.slim(),
-            team_player: *object.6,
-            transform: *object.7,
+            disk_status_effects: object.2.slim(),
+            disk_path_finder: object.3.slim(),
+            disk_controller: object.4.slim(),
+            disk_weapon_set: object.5.slim(),
+            disk_stance: object.6.slim(),
+            disk_orders: object.7.slim(),
+            disk_garrison: object.8.slim(),
+            disk_experience: object.9.slim().map(|veterancy| veterancy.experience()),
+            disk_velocity: object.10.slim(),
+            team_player: *object.11,
+            transform: *object.12,
         }
     }
 }
@@ -157,9 +194,14 @@ impl From<ArmadilloDisk> for SpawnObject {
             },
             disk_data: Some(ObjectDiskData {
                 health: value.disk_health,
+                status_effects: value.disk_status_effects,
                 path_finder: value.disk_path_finder,
                 navigator: value.disk_controller,
                 weapon_set: value.disk_weapon_set,
+                stance: value.disk_stance,
+                orders: value.disk_orders,
+                garrison: value.disk_garrison,
+                experience: value.disk_experience,
                 velocity: value.disk_velocity.map(|vel| vel.into()),
                 ..default()
             }),
@@ -0,0 +1,246 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+use avian3d::prelude::Collider;
+use bevy_mod_event_group::IntoGroup;
+use serde::{Serialize, Deserialize};
+use superstruct::*;
+use crate::*;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Component)]
+pub struct DefenseTurret;
+
+impl From<DefenseTurret> for ObjectType {
+    fn from(_: DefenseTurret) -> Self {
+        ObjectType::DefenseTurret
+    }
+}
+
+#[superstruct{
+    no_enum,
+    variants(Bundle, Prefab, Ghost, Disk),
+    variant_attributes(derive(Debug, Clone)),
+    specific_variant_attributes(
+        Bundle(derive(Bundle)),
+        Ghost(derive(Bundle)),
+        Disk(derive(Serialize, Deserialize)),
+    ),
+}]
+#[derive(Debug, Clone)]
+pub struct DefenseTurret {
+    #[superstruct(only(Prefab, Bundle))]        pub health: Health,
+    #[superstruct(only(Prefab, Bundle))]        pub death: Death,
+    #[superstruct(only(Bundle))]                pub status_effects: StatusEffects,
+    #[superstruct(only(Prefab, Bundle))]        pub weapon_set: WeaponSet,
+    #[superstruct(only(Prefab, Bundle))]        pub turrets: Turrets,
+    #[superstruct(only(Prefab, Bundle))]        pub collider: Collider,
+    #[superstruct(only(Bundle))]                pub defense_turret: DefenseTurret,
+    #[superstruct(only(Bundle))]                pub snowflake: Snowflake,
+    #[superstruct(only(Bundle))]                pub selectable: Selectable,
+    #[superstruct(only(Bundle, Ghost))]         pub object_type: ObjectType,
+    #[superstruct(only(Bundle, Ghost))]         pub visibility: Visibility,
+    #[superstruct(only(Bundle, Ghost, Disk))]   pub transform: Transform,
+    #[superstruct(only(Bundle, Disk))]          pub team_player: TeamPlayer,
+    #[superstruct(only(Disk))]                  pub disk_snowflake: Option<Snowflake>,
+    #[superstruct(only(Disk))]                  pub disk_health: Option<Health>,
+    #[superstruct(only(Disk))]                  pub disk_status_effects: Option<StatusEffects>,
+    #[superstruct(only(Disk))]                  pub disk_weapon_set: Option<WeaponSet>,
+    #[superstruct(only(Disk))]                  pub disk_turrets: Option<Turrets>,
+}
+
+impl TryFrom<&ObjectAsset> for DefenseTurretPrefab {
+    type Error = ContentError;
+    fn try_from(asset: &ObjectAsset) -> Result<Self, Self::Error> {
+        let Some(health) = asset.health else { return Err(ContentError::MissingHealth); };
+        let Some(weapon_set) = asset.weapon_set.clone() else { return Err(ContentError::MissingWeapons); };
+        let Some(turrets) = asset.turrets.clone() else { return Err(ContentError::MissingTurrets); };
+        let Some(collider_string) = asset.collider_string.clone() else { return Err(ContentError::MissingColliderString); };
+        let Some((vertices, indices)) = decode(collider_string) else { return Err(ContentError::ColliderDecodeError); };
+
+        let collider = Collider::trimesh(vertices, indices);
+
+        Ok(Self {
+            health,
+            death: asset.death.unwrap_or_default(),
+            weapon_set,
+            turrets: turrets.into(),
+            collider,
+        })
+    }
+}
+
+impl DefenseTurretBundle {
+    pub fn with_spawn_data(mut self, spawn_data: ObjectSpawnData) -> Self {
+        self.snowflake = spawn_data.snowflake;
+        self.team_player = spawn_data.teamplayer;
+        self.transform = spawn_data.transform;
+        self
+    }
+
+    pub fn with_disk_data(mut self, disk_data: Option<ObjectDiskData>) -> Self {
+        let Some(disk_data) = disk_data else { return self; };
+        if let Some(health) = disk_data.health { self.health = health; }
+        if let Some(status_effects) = disk_data.status_effects { self.status_effects = status_effects; }
+        if let Some(weapon_set) = disk_data.weapon_set { self.weapon_set = weapon_set; }
+        if let Some(turrets) = disk_data.turrets { self.turrets = turrets; }
+        self
+    }
+}
+
+impl From<DefenseTurretPrefab> for DefenseTurretBundle {
+    fn from(prefab: DefenseTurretPrefab) -> Self {
+        Self {
+            defense_turret: DefenseTurret,
+            object_type: DefenseTurret.into(),
+            snowflake: Snowflake::new(),
+            health: prefab.health,
+            death: prefab.death,
+            status_effects: StatusEffects::default(),
+            weapon_set: prefab.weapon_set.clone(),
+            turrets: prefab.turrets.clone(),
+            team_player: TeamPlayer::default(),
+            selectable: Selectable::single(),
+            collider: prefab.collider.clone(),
+            visibility: Visibility::default(),
+            transform: Transform::default(),
+        }
+    }
+}
+
+impl From<(DefenseTurretDisk, &DefenseTurretPrefab)> for DefenseTurretBundle {
+    fn from((save, prefab): (DefenseTurretDisk, &DefenseTurretPrefab)) -> Self {
+        Self {
+            defense_turret: DefenseTurret,
+            object_type: DefenseTurret.into(),
+            snowflake: save.disk_snowflake.unwrap_or(Snowflake::new()),
+            health: save.disk_health.unwrap_or(prefab.health),
+            death: prefab.death,
+            status_effects: save.disk_status_effects.unwrap_or_default(),
+            weapon_set: save.disk_weapon_set.unwrap_or(prefab.weapon_set.clone()),
+            turrets: save.disk_turrets.unwrap_or(prefab.turrets.clone()),
+            team_player: save.team_player,
+            selectable: Selectable::single(),
+            collider: prefab.collider.clone(),
+            visibility: Visibility::default(),
+            transform: save.transform.into(),
+        }
+    }
+}
+
+impl DefenseTurretGhost {
+    pub fn new() -> Self {
+        Self {
+            object_type: DefenseTurret.into(),
+            visibility: Visibility::default(),
+            transform: Transform::default(),
+        }
+    }
+}
+
+impl<'a> From<DefenseTurretDiskQuery<'a>> for DefenseTurretDisk {
+    fn from(object: DefenseTurretDiskQuery) -> Self {
+        Self {
+            disk_snowflake: Some(*object.0),
+            disk_health: object.1.slim(),
+            disk_status_effects: object.2.slim(),
+            disk_weapon_set: object.3.slim(),
+            disk_turrets: object.4.slim(),
+            team_player: *object.5,
+            transform: (*object.6).into(),
+        }
+    }
+}
+
+impl From<DefenseTurretDisk> for SpawnObject {
+    fn from(value: DefenseTurretDisk) -> Self {
+        Self {
+            object_type: ObjectType::DefenseTurret,
+            spawn_data: ObjectSpawnData {
+                snowflake: value.disk_snowflake.unwrap_or(Snowflake::new()),
+                teamplayer: value.team_player,
+                transform: value.transform.into(),
+            },
+            disk_data: Some(ObjectDiskData {
+                health: value.disk_health,
+                status_effects: value.disk_status_effects,
+                weapon_set: value.disk_weapon_set,
+                turrets: value.disk_turrets,
+                ..default()
+            }),
+            spawn_mode: SpawnMode::Load,
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+pub struct DefenseTurretPlugin;
+
+impl DefenseTurretPlugin {
+    pub fn spawn(
+        mut spawn_events: EventReader<SpawnObject<DefenseTurret>>,
+        mut client_requests: EventWriter<ClientRequest>,
+        prefabs: Res<ObjectPrefabs>,
+        mut status: ResMut<LoadingStatus>,
+        mut commands: Commands,
+    ) {
+        for event in spawn_events.read() {
+            commands.spawn(DefenseTurretBundle::from(prefabs.defense_turret_prefab.clone()).with_spawn_data(event.spawn_data.clone()).with_disk_data(event.disk_data.clone()));
+            match event.spawn_mode {
+                SpawnMode::Load => { status.defense_turrets_loaded = Some(true); },
+                SpawnMode::Spawn => { client_requests.write(ClientRequest::SpawnObject(event.clone().into_group())); },
+                SpawnMode::Fetch => { },
+            }
+        }
+    }
+
+    pub fn ghost(
+        mut ghost: Local<Option<Entity>>,
+        mut command_events: EventReader<CommandEvent>,
+        mut spawn_events: EventWriter<SpawnObject>,
+        mut commands: Commands,
+    ) {
+        for event in command_events.read() {
+            let Some(entity) = event.objects.first() else { return; };
+            let CommandType::Build(build) = &event.command else { continue; };
+            match build {
+                BuildStatus::Begin(building) => {
+                    let Ok(ObjectType::DefenseTurret) = ObjectType::try_from(building.clone()) else { continue; };
+                    let Ok(mut ghost_commands) = commands.get_entity(*entity) else { continue; };
+                    ghost_commands.insert(DefenseTurretGhost::new());
+                    *ghost = Some(ghost_commands.id());
+                }
+                BuildStatus::Finish(transform) => {
+                    if ghost.map_or(false, |ghost_entity| *entity == ghost_entity) {
+                        let spawn_data = ObjectSpawnData {
+                            snowflake: Snowflake::new(),
+                            teamplayer: event.player,
+                            transform: *transform,
+                        };
+
+                        let spawn_event = SpawnObject {
+                            object_type: ObjectType::DefenseTurret,
+                            spawn_data: spawn_data,
+                            disk_data: None,
+                            spawn_mode: SpawnMode::Spawn,
+                            phantom_data: PhantomData,
+                        };
+
+                        spawn_events.write(spawn_event);
+                    }
+                },
+            }
+        }
+    }
+}
+
+impl Plugin for DefenseTurretPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, (
+                Self::spawn,
+                Self::ghost,
+            ).run_if(resource_exists::<ObjectPrefabs>))
+        ;
+    }
+}
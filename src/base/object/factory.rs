@@ -30,7 +30,10 @@ impl From<Factory> for ObjectType {
 #[derive(Debug, Clone)]
 pub struct Factory {
     #[superstruct(only(Prefab, Bundle))]        pub health: Health,
+    #[superstruct(only(Prefab, Bundle))]        pub death: Death,
+    #[superstruct(only(Bundle))]                pub status_effects: StatusEffects,
     #[superstruct(only(Prefab, Bundle))]        pub queues: Queues,
+    #[superstruct(only(Prefab, Bundle))]        pub garrison: Garrison,
     #[superstruct(only(Prefab, Bundle))]        pub collider: Collider,
     #[superstruct(only(Bundle))]                pub factory: Factory,
     #[superstruct(only(Bundle, Ghost))]         pub object_type: ObjectType,
@@ -41,7 +44,9 @@ pub struct Factory {
     #[superstruct(only(Bundle, Ghost, Disk))]   pub transform: Transform,
     #[superstruct(only(Disk))]                  pub disk_snowflake: Option<Snowflake>,
     #[superstruct(only(Disk))]                  pub disk_health: Option<Health>,
+    #[superstruct(only(Disk))]                  pub disk_status_effects: Option<StatusEffects>,
     #[superstruct(only(Disk))]                  pub disk_queues: Option<Queues>,
+    #[superstruct(only(Disk))]                  pub disk_garrison: Option<Garrison>,
 }
 
 impl TryFrom<(&ObjectAsset, &HashMap<ObjectType, (ActiveQueue, StackData)>)> for FactoryPrefab {
@@ -57,7 +62,9 @@ impl TryFrom<(&ObjectAsset, &HashMap<ObjectType, (ActiveQueue, StackData)>)> for
 
         Ok(Self {
             health,
+            death: asset.death.unwrap_or_default(),
             queues,
+            garrison: asset.garrison.map(Garrison::from).unwrap_or_default(),
             collider,
         })
     }
@@ -73,7 +80,9 @@ impl FactoryBundle {
     pub fn with_disk_data(mut self, disk_data: Option<ObjectDiskData>) -> Self {
         let Some(disk_data) = disk_data else { return self; };
         if let Some(health) = disk_data.health { self.health = health; }
+        if let Some(status_effects) = disk_data.status_effects { self.status_effects = status_effects; }
         if let Some(queues) = disk_data.queues { self.queues = queues; }
+        if let Some(garrison) = disk_data.garrison { self.garrison = garrison; }
         self
     }
 }
@@ -86,7 +95,10 @@ impl From<FactoryPrefab> for FactoryBundle {
             object_type: Factory.into(),
             snowflake: Snowflake::new(),
             health: prefab.health,
+            death: prefab.death,
+            status_effects: StatusEffects::default(),
             queues: prefab.queues.clone(),
+            garrison: prefab.garrison.clone(),
             team_player: TeamPlayer::default(),
             selectable: Selectable::single(),
             collider: prefab.collider.clone(),
@@ -104,7 +116,10 @@ impl From<(FactoryDisk, &FactoryPrefab)> for FactoryBundle {
             object_type: Factory.into(),
             snowflake: save.disk_snowflake.unwrap_or(Snowflake::new()),
             health: save.disk_health.unwrap_or(prefab.health),
+            death: prefab.death,
+            status_effects: save.disk_status_effects.unwrap_or_default(),
             queues: save.disk_queues.unwrap_or(prefab.queues.clone()),
+            garrison: save.disk_garrison.unwrap_or(prefab.garrison.clone()),
             team_player: save.team_player,
             selectable: Selectable::single(),
             collider: prefab.collider.clone(),
@@ -131,9 +146,11 @@ impl<'a> From<FactoryDiskQuery<'a>> for FactoryDisk {
         Self {
             disk_snowflake: Some(*object.0),
             disk_health: object.1.slim(),
-            disk_queues: object.2.slim(),
-            team_player: *object.3,
-            transform: (*object.4).into(),
+            disk_status_effects: object.2.slim(),
+            disk_queues: object.3.slim(),
+            disk_garrison: object.4.slim(),
+            team_player: *object.5,
+            transform: (*object.6).into(),
         }
     }
 }
@@ -149,7 +166,9 @@ impl From<FactoryDisk> for SpawnObject {
             },
             disk_data: Some(ObjectDiskData {
                 health: value.disk_health,
+                status_effects: value.disk_status_effects,
                 queues: value.disk_queues,
+                garrison: value.disk_garrison,
                 ..default()
             }),
             spawn_mode: SpawnMode::Load,
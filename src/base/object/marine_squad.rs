@@ -28,9 +28,15 @@ impl From<MarineSquad> for ObjectType {
 }]
 #[derive(Debug, Clone)]
 pub struct MarineSquad {
+    #[superstruct(only(Prefab))]            pub stack: StackData,
     #[superstruct(only(Prefab, Bundle))]    pub health: Health,
+    #[superstruct(only(Prefab, Bundle))]    pub death: Death,
+    #[superstruct(only(Bundle))]            pub status_effects: StatusEffects,
     #[superstruct(only(Prefab, Bundle))]    pub controller: Navigator,
     #[superstruct(only(Prefab, Bundle))]    pub weapon_set: WeaponSet,
+    #[superstruct(only(Prefab, Bundle))]    pub stance: Stance,
+    #[superstruct(only(Bundle))]            pub orders: Orders,
+    #[superstruct(only(Prefab, Bundle))]    pub veterancy: Veterancy,
     #[superstruct(only(Prefab, Bundle))]    pub squad: Squad,
     #[superstruct(only(Prefab, Bundle))]    pub collider: Collider,
     #[superstruct(only(Bundle))]            pub marker: MarineSquad,
@@ -45,16 +51,21 @@ pub struct MarineSquad {
     #[superstruct(only(Bundle, Disk))]      pub transform: Transform,
     #[superstruct(only(Disk))]              pub disk_snowflake: Option<Snowflake>,
     #[superstruct(only(Disk))]              pub disk_health: Option<Health>,
+    #[superstruct(only(Disk))]              pub disk_status_effects: Option<StatusEffects>,
     #[superstruct(only(Disk))]              pub disk_squad: Option<Squad>,
     #[superstruct(only(Disk))]              pub disk_path_finder: Option<PathFinder>,
     #[superstruct(only(Disk))]              pub disk_controller: Option<Navigator>,
     #[superstruct(only(Disk))]              pub disk_weapon_set: Option<WeaponSet>,
+    #[superstruct(only(Disk))]              pub disk_stance: Option<Stance>,
+    #[superstruct(only(Disk))]              pub disk_orders: Option<Orders>,
+    #[superstruct(only(Disk))]              pub disk_experience: Option<f32>,
     #[superstruct(only(Disk))]              pub disk_velocity: Option<LinearVelocity>,
 }
 
 impl TryFrom<&ObjectAsset> for MarineSquadPrefab {
     type Error = ContentError;
     fn try_from(asset: &ObjectAsset) -> Result<Self, Self::Error> {
+        let Some((_, stack)) = asset.stack.clone() else { return Err(ContentError::MissingStack); };
         let Some(health) = asset.health else { return Err(ContentError::MissingHealth); };
         let Some(asset_squad) = asset.asset_squad.clone() else { return Err(ContentError::MissingSquad); };
         let Some(controller) = asset.navigator else { return Err(ContentError::MissingController); };
@@ -66,10 +77,14 @@ impl TryFrom<&ObjectAsset> for MarineSquadPrefab {
         let collider = Collider::trimesh(vertices, indices);
 
         Ok(Self {
+            stack,
             health,
+            death: asset.death.unwrap_or_default(),
             squad: asset_squad.into(),
             controller,
             weapon_set,
+            stance: asset.stance.unwrap_or_default(),
+            veterancy: Veterancy::new(asset.veterancy.clone().unwrap_or_default(), health.max_health()),
             collider,
         })
     }
@@ -86,10 +101,14 @@ impl MarineSquadBundle {
     pub fn with_disk_data(mut self, disk_data: Option<ObjectDiskData>) -> Self {
         let Some(disk_data) = disk_data else { return self; };
         if let Some(health) = disk_data.health { self.health = health; }
+        if let Some(status_effects) = disk_data.status_effects { self.status_effects = status_effects; }
         if let Some(squad) = disk_data.squad { self.squad = squad; }
         if let Some(path_finder) = disk_data.path_finder { self.path_finder = path_finder; }
         if let Some(controller) = disk_data.navigator { self.controller = controller; }
         if let Some(weapon_set) = disk_data.weapon_set { self.weapon_set = weapon_set; }
+        if let Some(stance) = disk_data.stance { self.stance = stance; }
+        if let Some(orders) = disk_data.orders { self.orders = orders; }
+        if let Some(experience) = disk_data.experience { self.veterancy.set_experience(experience); }
         if let Some(velocity) = disk_data.velocity { self.velocity = velocity; }
         self
     }
@@ -102,10 +121,15 @@ impl From<MarineSquadPrefab> for MarineSquadBundle {
             object_type: MarineSquad.into(),
             snowflake: Snowflake::new(),
             health: prefab.health,
+            death: prefab.death,
+            status_effects: StatusEffects::default(),
             squad: prefab.squad,
             path_finder: PathFinder::default(),
             controller: prefab.controller,
             weapon_set: prefab.weapon_set,
+            stance: prefab.stance,
+            orders: Orders::default(),
+            veterancy: prefab.veterancy.clone(),
             team_player: TeamPlayer::default(),
             selectable: Selectable::multiselect(),
             velocity: LinearVelocity::default(),
@@ -124,10 +148,15 @@ impl From<(MarineSquadDisk, &MarineSquadPrefab)> for MarineSquadBundle {
             object_type: MarineSquad.into(),
             snowflake: save.disk_snowflake.unwrap_or(Snowflake::new()),
             health: save.disk_health.unwrap_or(prefab.health),
+            death: prefab.death,
+            status_effects: save.disk_status_effects.unwrap_or_default(),
             squad: save.disk_squad.unwrap_or_else(|| prefab.squad.clone()),
             path_finder: save.disk_path_finder.unwrap_or_default(),
             controller: save.disk_controller.unwrap_or(prefab.controller),
             weapon_set: save.disk_weapon_set.unwrap_or(prefab.weapon_set.clone()),
+            stance: save.disk_stance.unwrap_or(prefab.stance),
+            orders: save.disk_orders.unwrap_or_default(),
+            veterancy: prefab.veterancy.clone().with_experience(save.disk_experience),
             team_player: save.team_player,
             selectable: Selectable::multiselect(),
             velocity: save.disk_velocity.unwrap_or(LinearVelocity::default()),
@@ -144,13 +173,17 @@ impl<'a> From<MarineSquadDiskQuery<'a>> for MarineSquadDisk {
         Self {
             disk_snowflake: Some(*object.0),
             disk_health: object.1.slim(),
-            disk_squad: object.2.slim(),
-            disk_path_finder: object.3.slim(),
-            disk_controller: object.4.slim(),
-            disk_weapon_set: object.5.slim(),
-            disk_velocity: (*object.6).slim(),
-            team_player: *object.7,
-            transform: (*object.8).into(),
+            disk_status_effects: object.2.slim(),
+            disk_squad: object.3.slim(),
+            disk_path_finder: object.4.slim(),
+            disk_controller: object.5.slim(),
+            disk_weapon_set: object.6.slim(),
+            disk_stance: object.7.slim(),
+            disk_orders: object.8.slim(),
+            disk_experience: object.9.slim().map(|veterancy| veterancy.experience()),
+            disk_velocity: (*object.10).slim(),
+            team_player: *object.11,
+            transform: (*object.12).into(),
         }
     }
 }
@@ -160,16 +193,20 @@ impl From<MarineSquadDisk> for SpawnObject {
         Self {
             object_type: ObjectType::MarineSquad,
             spawn_data: ObjectSpawnData {
-                snowflake: Snowflake::new(),
+                snowflake: value.disk_snowflake.unwrap_or(Snowflake::new()),
                 teamplayer: value.team_player,
                 transform: value.transform.into(),
             },
             disk_data: Some(ObjectDiskData {
                 health: value.disk_health,
+                status_effects: value.disk_status_effects,
                 squad: value.disk_squad,
                 path_finder: value.disk_path_finder,
                 navigator: value.disk_controller,
                 weapon_set: value.disk_weapon_set,
+                stance: value.disk_stance,
+                orders: value.disk_orders,
+                experience: value.disk_experience,
                 velocity: value.disk_velocity.map(|vel| vel.into()),
                 ..default()
             }),
@@ -239,9 +276,27 @@ impl Default for MarineSpawnPoints {
     }
 }
 
+///How close a squad must be to a friendly barracks to be reinforced.
+pub const REINFORCE_RANGE: f32 = 40.0;
+
+///A barracks finished the reinforcements queued for the squad with this snowflake.
+#[derive(Debug, Clone, Copy)]
+#[derive(Event)]
+pub struct ReinforceEvent(pub Snowflake);
+
 pub struct MarineSquadPlugin;
 
 impl MarineSquadPlugin {
+    fn spawn_member(object_type: &ObjectType, point: Option<&Vec2>, squad: Entity, commands: &mut Commands) -> Option<Entity> {
+        let transform = Transform::from_translation(Vec3::from((*point?, 0.0)).xzy());
+        match object_type {
+            ObjectType::Marine => {
+                Some(commands.spawn((MarineBundle::default().with_transform(transform), ChildOf(squad))).id())
+            },
+            _ => { None },
+        }
+    }
+
     pub fn spawn(
         points: Local<MarineSpawnPoints>,
         mut spawn_events: EventReader<SpawnObject<MarineSquad>>,
@@ -251,18 +306,12 @@ impl MarineSquadPlugin {
         mut commands: Commands,
     ) {
         for event in spawn_events.read() {
-            commands.spawn(MarineSquadBundle::from(prefabs.marine_squad_prefab.clone()).with_spawn_data(event.spawn_data.clone()).with_disk_data(event.disk_data.clone())).with_children(|parent| {
-                let squad = event.disk_data.clone().and_then(|disk_data| disk_data.squad).unwrap_or(prefabs.marine_squad_prefab.squad.clone());
-                for ((object_type, _), point) in squad.members.iter().zip(points.0.iter()) {
-                    let transform = Transform::from_translation(Vec3::from((*point, 0.0)).xzy());
-                    match object_type {
-                        ObjectType::Marine => {
-                            parent.spawn(MarineBundle::default().with_transform(transform));
-                        },
-                        _ => { },
-                    };
-                }
-            });
+            let mut squad = event.disk_data.clone().and_then(|disk_data| disk_data.squad).unwrap_or(prefabs.marine_squad_prefab.squad.clone());
+            let squad_entity = commands.spawn(MarineSquadBundle::from(prefabs.marine_squad_prefab.clone()).with_spawn_data(event.spawn_data.clone()).with_disk_data(event.disk_data.clone())).id();
+            for (index, (object_type, member)) in squad.members.iter_mut().enumerate() {
+                *member = Self::spawn_member(object_type, points.get(index), squad_entity, &mut commands);
+            }
+            commands.entity(squad_entity).insert(squad);
             match event.spawn_mode {
                 SpawnMode::Load => { status.marines_loaded = Some(true); },
                 SpawnMode::Spawn => { client_requests.write(ClientRequest::SpawnObject(event.clone().into_group())); },
@@ -270,14 +319,83 @@ impl MarineSquadPlugin {
             }
         }
     }
+
+    pub fn casualties(
+        mut squads: Query<(&Health, &mut Squad), Changed<Health>>,
+        mut commands: Commands,
+    ) {
+        squads.iter_mut().for_each(|(health, mut squad)| {
+            let survivors = squad.survivors(health.health_percent());
+            while squad.members.len() > survivors {
+                let Some((_, member)) = squad.members.pop() else { break; };
+                if let Some(member) = member {
+                    commands.entity(member).despawn();
+                }
+            }
+        });
+    }
+
+    ///Queues reinforcements for each commanded squad at the nearest friendly barracks in range. Cost and build time are
+    ///the share of a new squad that is missing, paid while the barracks works on it like any other order.
+    pub fn reinforce(
+        mut command_events: EventReader<CommandEvent>,
+        prefabs: Res<ObjectPrefabs>,
+        squads: Query<(&Transform, &TeamPlayer, &Snowflake, &Squad), (With<MarineSquad>, Without<Dying>)>,
+        mut barracks: Query<(&Transform, &TeamPlayer, &mut Queues), With<Barracks>>,
+    ) {
+        for event in command_events.read() {
+            let CommandType::Reinforce = event.command else { continue; };
+            for entity in event.objects.iter() {
+                let Ok((transform, teamplayer, snowflake, squad)) = squads.get(*entity) else { continue; };
+                if *teamplayer != event.player || squad.missing() == 0 { continue; }
+                let position = transform.translation.xz();
+                let Some(queue) = barracks.iter_mut()
+                    .filter(|(barracks_transform, barracks_teamplayer, _)| **barracks_teamplayer == event.player && barracks_transform.translation.xz().distance(position) <= REINFORCE_RANGE)
+                    .min_by(|(a, _, _), (b, _, _)| a.translation.xz().distance(position).total_cmp(&b.translation.xz().distance(position)))
+                    .and_then(|(_, _, queues)| queues.into_inner().queues.get_mut(&ActiveQueue::Infantry)) else { continue; };
+                if queue.zip_queue.spine().iter().chain(queue.buffer.spine().iter()).any(|stack| stack.target == Some(*snowflake)) { continue; }
+
+                let share = squad.missing() as f64 / squad.full_strength() as f64;
+                let stack = &prefabs.marine_squad_prefab.stack;
+                queue.enqueue(StackData {
+                    time_to_build: stack.time_to_build.mul_f64(share),
+                    cost: (stack.cost as f64 * share).ceil() as u128,
+                    target: Some(*snowflake),
+                    ..stack.clone()
+                });
+            }
+        }
+    }
+
+    ///Brings squads whose reinforcements have finished back to full strength.
+    pub fn reinforce_squads(
+        points: Local<MarineSpawnPoints>,
+        mut reinforcements: EventReader<ReinforceEvent>,
+        mut squads: Query<(Entity, &Snowflake, &mut Squad, &mut Health), (With<MarineSquad>, Without<Dying>)>,
+        mut commands: Commands,
+    ) {
+        for ReinforceEvent(target) in reinforcements.read() {
+            let Some((squad_entity, _, mut squad, mut health)) = squads.iter_mut().find(|(_, snowflake, _, _)| **snowflake == *target) else { continue; };
+            let full = squad.roster.clone();
+            for (index, object_type) in full.iter().enumerate().skip(squad.members.len()) {
+                let member = Self::spawn_member(object_type, points.get(index), squad_entity, &mut commands);
+                squad.members.push((object_type.clone(), member));
+            }
+            health.restore();
+        }
+    }
 }
 
 impl Plugin for MarineSquadPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_systems(Update, (
-                Self::spawn
+                Self::spawn,
+                Self::casualties,
+                Self::reinforce,
+                Self::reinforce_squads,
             ).run_if(resource_exists::<ObjectPrefabs>))
+            .add_event::<ReinforceEvent>()
         ;
     }
 }
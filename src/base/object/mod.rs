@@ -1,6 +1,7 @@
 pub mod armadillo;
 pub mod barracks;
 pub mod crane_yard;
+pub mod defense_turret;
 pub mod factory;
 pub mod marine_squad;
 pub mod resource_node;
@@ -9,6 +10,7 @@ pub mod tank;
 pub use armadillo::*;
 pub use barracks::*;
 pub use crane_yard::*;
+pub use defense_turret::*;
 pub use factory::*;
 pub use marine_squad::*;
 pub use resource_node::*;
@@ -31,6 +33,7 @@ pub enum ObjectType {
     ResourcePlatformClaimed,
     Barracks,
     Factory,
+    DefenseTurret,
     CommunicationsCenter,
     MarineSquad,
     Marine,
@@ -48,6 +51,7 @@ impl Display for ObjectType {
             ObjectType::ResourcePlatformClaimed => write!(f, "Resource Platform"),
             ObjectType::Barracks => write!(f, "Barracks"),
             ObjectType::Factory => write!(f, "Factory"),
+            ObjectType::DefenseTurret => write!(f, "Defense Turret"),
             ObjectType::CommunicationsCenter => write!(f, "Communications Center"),
             ObjectType::MarineSquad => write!(f, "Marine Squad"),
             ObjectType::Marine => write!(f, "Marine"),
@@ -67,7 +71,7 @@ pub enum SpawnMode {
 
 #[event_group(Debug, Clone, Serialize, Deserialize, Event,)]
 pub struct SpawnObject {
-    #[events(CraneYard, Barracks, Factory, ResourceNode, MarineSquad, Armadillo, TankBase)]
+    #[events(CraneYard, Barracks, Factory, DefenseTurret, ResourceNode, MarineSquad, Armadillo, TankBase)]
     pub object_type: ObjectType,
     pub spawn_data: ObjectSpawnData,
     pub disk_data: Option<ObjectDiskData>,
@@ -91,11 +95,16 @@ impl From<ObjectSpawnData> for (Snowflake, TeamPlayer, Transform) {
 #[derive(Serialize, Deserialize)]
 pub struct ObjectDiskData {
     pub health: Option<Health>,
+    pub status_effects: Option<StatusEffects>,
     pub queues: Option<Queues>,
+    pub garrison: Option<Garrison>,
     pub path_finder: Option<PathFinder>,
     pub navigator: Option<Navigator>,
     pub weapon_set: Option<WeaponSet>,
-    pub reference: Option<Reference>,
+    pub stance: Option<Stance>,
+    pub orders: Option<Orders>,
+    pub experience: Option<f32>,
+    pub turrets: Option<Turrets>,
     pub squad: Option<Squad>,
     pub velocity: Option<LinearVelocity>,
     pub resource_node: Option<ResourceNodePlatforms>
@@ -108,11 +117,16 @@ pub struct ObjectAsset {
     pub stack: Option<(ActiveQueue, StackData)>,
     pub health: Option<Health>,
     pub asset_queues: Option<AssetQueues>,
+    pub garrison: Option<AssetGarrison>,
     pub economic_object: Option<EconomicObject>,
     pub asset_squad: Option<AssetSquad>,
     pub navigator: Option<Navigator>,
     pub weapon_set: Option<WeaponSet>,
-    pub reference: Option<Reference>,
+    pub stance: Option<Stance>,
+    pub veterancy: Option<AssetVeterancy>,
+    pub turrets: Option<Vec<TurretData>>,
+    pub death: Option<Death>,
+    pub footprint: Option<Footprint>,
     pub collider_string: Option<String>,
 }
 
@@ -157,6 +171,8 @@ pub struct ObjectAssets {
     pub barracks: Handle<ObjectAsset>,
     #[asset(path = "objects/factory.ron")]
     pub factory: Handle<ObjectAsset>,
+    #[asset(path = "objects/defense_turret.ron")]
+    pub defense_turret: Handle<ObjectAsset>,
     #[asset(path = "objects/marine_squad.ron")]
     pub marine_squad: Handle<ObjectAsset>,
     #[asset(path = "objects/armadillo.ron")]
@@ -165,6 +181,24 @@ pub struct ObjectAssets {
     pub tank: Handle<ObjectAsset>,
 }
 
+impl ObjectAssets {
+    pub fn get_object(&self, object_type: ObjectType) -> Option<&Handle<ObjectAsset>> {
+        match object_type {
+            ObjectType::CraneYard => Some(&self.crane_yard),
+            ObjectType::ResourceNode => Some(&self.resource_node),
+            ObjectType::ResourcePlatformUnclaimed => Some(&self.resource_platform_unclaimed),
+            ObjectType::ResourcePlatformClaimed => Some(&self.resource_platform_claimed),
+            ObjectType::Barracks => Some(&self.barracks),
+            ObjectType::Factory => Some(&self.factory),
+            ObjectType::DefenseTurret => Some(&self.defense_turret),
+            ObjectType::MarineSquad => Some(&self.marine_squad),
+            ObjectType::Armadillo => Some(&self.armadillo),
+            ObjectType::TankBase => Some(&self.tank),
+            _ => None
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 #[derive(Resource)]
 pub struct ObjectPrefabs {
@@ -174,6 +208,7 @@ pub struct ObjectPrefabs {
     pub resource_platform_claimed_prefab: ResourcePlatformClaimedPrefab,
     pub barracks_prefab: BarracksPrefab,
     pub factory_prefab: FactoryPrefab,
+    pub defense_turret_prefab: DefenseTurretPrefab,
     pub marine_squad_prefab: MarineSquadPrefab,
     pub armadillo_prefab: ArmadilloPrefab,
     pub tank_prefab: TankBasePrefab,
@@ -191,6 +226,7 @@ impl FromWorld for ObjectPrefabs {
         let resource_platform_unclaimed_prefab_asset = assets.get(&objects.resource_platform_unclaimed).expect("Failed to load resource_platform_unclaimed");
         let barracks_prefab_asset = assets.get(&objects.barracks).expect("Failed to load barracks");
         let factory_prefab_asset = assets.get(&objects.factory).expect("Failed to load factory");
+        let defense_turret_prefab_asset = assets.get(&objects.defense_turret).expect("Failed to load defense_turret");
         let marine_squad_prefab_asset = assets.get(&objects.marine_squad).expect("Failed to load marine_squad");
         let armadillo_prefab_asset = assets.get(&objects.armadillo).expect("Failed to load tank");
         let tank_prefab_asset = assets.get(&objects.tank).expect("Failed to load tank");
@@ -199,6 +235,7 @@ impl FromWorld for ObjectPrefabs {
 
         stacks.insert(ObjectType::Barracks, barracks_prefab_asset.stack.clone().unwrap());
         stacks.insert(ObjectType::Factory, factory_prefab_asset.stack.clone().unwrap());
+        stacks.insert(ObjectType::DefenseTurret, defense_turret_prefab_asset.stack.clone().unwrap());
         stacks.insert(ObjectType::MarineSquad, marine_squad_prefab_asset.stack.clone().unwrap());
         stacks.insert(ObjectType::Armadillo, armadillo_prefab_asset.stack.clone().unwrap());
         stacks.insert(ObjectType::TankBase, tank_prefab_asset.stack.clone().unwrap());
@@ -209,6 +246,7 @@ impl FromWorld for ObjectPrefabs {
         let resource_platform_unclaimed_prefab = ResourcePlatformUnclaimedPrefab::try_from(resource_platform_unclaimed_prefab_asset).unwrap();
         let barracks_prefab = BarracksPrefab::try_from((barracks_prefab_asset, &stacks)).unwrap();
         let factory_prefab = FactoryPrefab::try_from((factory_prefab_asset, &stacks)).unwrap();
+        let defense_turret_prefab = DefenseTurretPrefab::try_from(defense_turret_prefab_asset).unwrap();
         let marine_squad_prefab = MarineSquadPrefab::try_from(marine_squad_prefab_asset).unwrap();
         let armadillo_prefab = ArmadilloPrefab::try_from(armadillo_prefab_asset).unwrap();
         let tank_prefab = TankBasePrefab::try_from(tank_prefab_asset).unwrap();
@@ -220,6 +258,7 @@ impl FromWorld for ObjectPrefabs {
             resource_platform_claimed_prefab,
             barracks_prefab,
             factory_prefab,
+            defense_turret_prefab,
             marine_squad_prefab,
             armadillo_prefab,
             tank_prefab,
@@ -234,28 +273,27 @@ pub struct ObjectPlugin;
 impl ObjectPlugin {
     pub fn patch_grid_spawn(
         mut grid_map: ResMut<GridMap>,
+        mut footprints: ResMut<GridFootprints>,
         pathing_space: Res<GridSpace>,
-        objects: Query<(&Transform, &ObjectType), (Added<ObjectType>, With<Collider>)>,
+        object_assets: Option<Res<ObjectAssets>>,
+        assets: Res<Assets<ObjectAsset>>,
+        objects: Query<(Entity, &Transform, &ObjectType, &Collider), (Added<ObjectType>, Without<PathFinder>)>,
     ) {
+        if grid_map.is_added() {
+            footprints.clear();
+        }
         let mut recompute = false;
-        objects.iter().for_each(|(transform, object_type)| {
-            let max = match object_type {
-                ObjectType::CraneYard => { Some((8, 8)) },
-                ObjectType::Factory => { Some((11, 11)) },
-                ObjectType::ResourceNode => { Some((9, 9))}
-                _ => { None }
-            };
-            if let Some((x_max, y_max)) = max {
-                let mut blocks = Vec::new();
-                for x_offset in -x_max..=x_max {
-                    for y_offset in -y_max..=y_max {
-                        let (x, y) = pathing_space.position_to_index(transform.translation.xz() + Vec2::new(x_offset as f32, y_offset as f32));
-                        blocks.push((x, y));
-                    }
-                }
-                grid_map.0.add_objects(blocks);
-                recompute = true;
-            }
+        objects.iter().for_each(|(entity, transform, object_type, collider)| {
+            let footprint = object_assets.as_ref()
+                .and_then(|object_assets| object_assets.get_object(*object_type))
+                .and_then(|handle| assets.get(handle))
+                .and_then(|asset| asset.footprint)
+                .unwrap_or_default();
+            let blocks = footprint.cells(transform, collider, &pathing_space);
+            if blocks.is_empty() { return; }
+            grid_map.0.add_objects(blocks.clone());
+            footprints.insert(entity, blocks);
+            recompute = true;
         });
         if recompute {
             grid_map.0.precompute();
@@ -264,41 +302,68 @@ impl ObjectPlugin {
 
     pub fn patch_grid_kill(
         mut grid_map: ResMut<GridMap>,
-        pathing_space: Res<GridSpace>,
-        mut kills: EventReader<ObjectKilledEvent>,
-        objects: Query<(&Transform, &ObjectType), With<Collider>>,
+        mut footprints: ResMut<GridFootprints>,
+        terrain: Res<TerrainBlocks>,
+        mut removed: EventReader<ObjectRemovedEvent>,
     ) {
         let mut recompute = false;
-        for kill in kills.read() {
-            let Ok((transform, object_type)) = objects.get(kill.0) else { continue; };
-            let max = match object_type {
-                ObjectType::CraneYard => { Some((8, 8)) },
-                ObjectType::Factory => { Some((11, 11)) },
-                ObjectType::ResourceNode => { Some((9, 9))}
-                _ => { None }
-            };
-            if let Some((x_max, y_max)) = max {
-                let mut blocks = Vec::new();
-                for x_offset in -x_max..=x_max {
-                    for y_offset in -y_max..=y_max {
-                        let (x, y) = pathing_space.position_to_index(transform.translation.xz() + Vec2::new(x_offset as f32, y_offset as f32));
-                        blocks.push((x, y));
-                    }
+        for ObjectRemovedEvent { entity, .. } in removed.read() {
+            let Some(blocks) = footprints.remove(entity) else { continue; };
+            grid_map.0.remove_objects(terrain.removable(blocks));
+            recompute = true;
+        }
+        if recompute {
+            grid_map.0.precompute();
+        }
+    }
+
+    pub fn patch_grid_wrecks(
+        time: Res<Time>,
+        mut grid_map: ResMut<GridMap>,
+        pathing_space: Res<GridSpace>,
+        terrain: Res<TerrainBlocks>,
+        mut wrecks: Query<(Entity, &Transform, &mut Wreck)>,
+        mut commands: Commands,
+    ) {
+        let blocks = |transform: &Transform, (x_max, y_max): (isize, isize)| {
+            let mut blocks = Vec::new();
+            for x_offset in -x_max..=x_max {
+                for y_offset in -y_max..=y_max {
+                    blocks.push(pathing_space.position_to_index(transform.translation.xz() + Vec2::new(x_offset as f32, y_offset as f32)));
                 }
-                grid_map.0.remove_objects(blocks);
+            }
+            blocks
+        };
+
+        let mut recompute = false;
+        wrecks.iter_mut().for_each(|(entity, transform, mut wreck)| {
+            if wreck.is_added() {
+                grid_map.0.add_objects(blocks(transform, wreck.half_extents));
                 recompute = true;
             }
-        }
+            let Some(remaining) = wreck.remaining.as_mut() else { return; };
+            *remaining -= time.delta_secs();
+            if *remaining > 0.0 { return; }
+            grid_map.0.remove_objects(terrain.removable(blocks(transform, wreck.half_extents)));
+            recompute = true;
+            commands.entity(entity).despawn();
+        });
         if recompute {
             grid_map.0.precompute();
         }
     }
 
     pub fn show_grid(
+        input: Option<Res<ButtonInput<KeyCode>>>,
         grid_map: ResMut<GridMap>,
         pathing_space: Res<GridSpace>,
+        service: Option<Res<PathFindingService>>,
+        mut grid_layer: ResMut<GridLayerGizmos>,
         mut gizmos: Gizmos,
     ) {
+        if input.is_some_and(|input| input.just_pressed(KeyCode::F4)) {
+            grid_layer.0 = (grid_layer.0 + 1) % (MAX_CLEARANCE + 1);
+        }
         for object in grid_map.0.blocks() {
             let xy = pathing_space.index_to_position(*object);
             let xyz = xy.extend(0.0).xzy();
@@ -309,6 +374,14 @@ impl ObjectPlugin {
                 gizmos.line(xyz, xyz + Vec3::Y * 20.0, Srgba::BLUE);
             }
         }
+        if grid_layer.0 == 0 { return; }
+        let Some(layer) = service.and_then(|service| service.layer(grid_layer.0)) else { return; };
+        for object in layer.0.blocks() {
+            if grid_map.0.object_nodes(*object).is_some() { continue; }
+            let xy = pathing_space.index_to_position(*object);
+            let xyz = xy.extend(0.0).xzy();
+            gizmos.line(xyz, xyz + Vec3::Y * 5.0, Srgba::rgb(1.0, 0.5, 0.0));
+        }
 
     }
 
@@ -333,16 +406,19 @@ impl Plugin for ObjectPlugin {
         app
             .add_event_group::<SpawnObject>()
             .add_event::<ObjectKilledEvent>()
+            .init_resource::<GridFootprints>()
+            .init_resource::<GridLayerGizmos>()
             .add_plugins((
                 CraneYardPlugin,
                 ResourceNodePlugin,
                 BarracksPlugin,
                 FactoryPlugin,
+                DefenseTurretPlugin,
                 MarineSquadPlugin,
                 ArmadilloPlugin,
                 TankPlugin,
             ))
-            .add_systems(Update, (Self::patch_grid_spawn, Self::patch_grid_kill, Self::show_grid, Self::spawn_object.run_if(resource_exists::<GltfAssets>)))
+            .add_systems(Update, (Self::patch_grid_spawn, Self::patch_grid_kill, Self::patch_grid_wrecks, Self::show_grid, Self::spawn_object.run_if(resource_exists::<GltfAssets>)))
         ;
     }
-}
\ No newline at end of file
+}
\ No newline at end of file
@@ -183,6 +183,7 @@ impl From<ResourcePlatformClaimedMarker> for ObjectType {
 pub struct ResourcePlatformClaimed {
     #[superstruct(only(Prefab))]            pub stack: StackData,
     #[superstruct(only(Prefab, Bundle))]    pub health: Health,
+    #[superstruct(only(Bundle))]            pub status_effects: StatusEffects,
     #[superstruct(only(Prefab, Bundle))]    pub economic_object: EconomicObject,
     #[superstruct(only(Prefab, Bundle))]    pub collider: Collider,
     #[superstruct(only(Bundle))]            pub resource_platform_claimed_marker: ResourcePlatformClaimedMarker,
@@ -237,6 +238,7 @@ impl From<ResourcePlatformClaimedPrefab> for ResourcePlatformClaimedBundle {
             object_type: ResourcePlatformClaimedMarker.into(),
             snowflake: Snowflake::new(),
             health: prefab.health,
+            status_effects: StatusEffects::default(),
             economic_object: prefab.economic_object,
             team_player: TeamPlayer::default(),
             selectable: Selectable::single(),
@@ -459,7 +461,6 @@ impl ResourceNodePlugin {
                     node.0[platform.0.unwrap().1] = ResourcePlatform::Unclaimed;
                 }
                 commands.spawn(ResourcePlatformUnclaimedBundle::from(prefabs.resource_platform_unclaimed_prefab.clone()).with_platform(*platform).with_spawn_data(spawn_data));
-                commands.entity(event.0).despawn();
             }
         }
     }
@@ -765,17 +765,98 @@ impl CameraPlugin {
     }
 
     pub fn command_system(
+        mut drag_start: Local<Option<Vec2>>,
         mut unit_commands: EventWriter<CommandEvent>,
+        mut formation: ResMut<SelectedFormation>,
         player: Res<LocalPlayer>,
         cast: Res<CameraRaycast>,
         current_placement: Res<CurrentPlacement<CLICK_BUFFER>>,
         input: Res<ButtonInput<MouseButton>>,
+        key_input: Res<ButtonInput<KeyCode>>,
 
         units: Query<(Entity, &Selectable), With<PathFinder>>,
+        garrisons: Query<(Entity, &Selectable, &Garrison)>,
         team_players: Query<&TeamPlayer>,
+        snowflakes: Query<&Snowflake>,
         combat_world: Res<CombatWorld>,
     ) {
         if current_placement.placing() { return; }
+        if key_input.just_pressed(KeyCode::KeyF) {
+            formation.0 = formation.next();
+        }
+        if input.just_pressed(MouseButton::Right) {
+            *drag_start = cast.current_cast.map(|ray_cast| ray_cast.point.xz());
+        }
+        if key_input.just_pressed(KeyCode::KeyX) {
+            unit_commands.write(CommandEvent {
+                player: player.0,
+                objects: garrisons.iter().filter_map(|(id, sel, garrison)| (sel.selected && !garrison.is_empty()).then_some(id)).collect(),
+                command: CommandType::Ungarrison,
+            });
+        }
+        if key_input.just_pressed(KeyCode::KeyR) {
+            unit_commands.write(CommandEvent {
+                player: player.0,
+                objects: units.iter().filter_map(|(id, sel)| if sel.selected { Some(id) } else { None }).collect(),
+                command: CommandType::Reinforce,
+            });
+        }
+        if input.just_released(MouseButton::Right) && key_input.pressed(KeyCode::KeyU) {
+            if let Some(ray_cast) = cast.current_cast {
+                unit_commands.write(CommandEvent {
+                    player: player.0,
+                    objects: garrisons.iter().filter_map(|(id, sel, garrison)| (sel.selected && !garrison.is_empty()).then_some(id)).collect(),
+                    command: CommandType::Unload(ray_cast.point.xz()),
+                });
+            }
+            return;
+        }
+        let (guard, follow) = (key_input.pressed(KeyCode::KeyH), key_input.pressed(KeyCode::KeyT));
+        if input.just_released(MouseButton::Right) && (guard || follow) {
+            let friendly = cast.current_cast.map(|ray_cast| ray_cast.entity)
+                .filter(|entity| team_players.get(*entity).map_or(false, |teamplayer| teamplayer.team() == player.0.team()));
+            if let Some(target) = friendly {
+                unit_commands.write(CommandEvent {
+                    player: player.0,
+                    objects: units.iter().filter_map(|(id, sel)| if sel.selected && id != target { Some(id) } else { None }).collect(),
+                    command: if guard { CommandType::Guard(target) } else { CommandType::Follow(target) },
+                });
+            }
+            return;
+        }
+        if input.just_released(MouseButton::Right) && key_input.pressed(KeyCode::KeyV) {
+            if let Some(ray_cast) = cast.current_cast {
+                unit_commands.write(CommandEvent {
+                    player: player.0,
+                    objects: units.iter().filter_map(|(id, sel)| if sel.selected { Some(id) } else { None }).collect(),
+                    command: CommandType::ReverseMove(ray_cast.point.xz()),
+                });
+            }
+            return;
+        }
+        let shift = key_input.pressed(KeyCode::ShiftLeft) || key_input.pressed(KeyCode::ShiftRight);
+        let patrol = key_input.pressed(KeyCode::KeyP);
+        let attack_move = key_input.pressed(KeyCode::KeyG);
+        if input.just_released(MouseButton::Right) && (shift || patrol || attack_move) {
+            if let Some(ray_cast) = cast.current_cast {
+                let point = ray_cast.point.xz();
+                let enemy = combat_world.is_enemy(ray_cast.entity, player.0, &team_players).map_or(false, |t| t);
+                let command = match (shift, patrol, attack_move) {
+                    (true, true, _) => CommandType::Enqueue(Order::Patrol(point)),
+                    (true, false, true) => CommandType::Enqueue(Order::AttackMove(point)),
+                    (true, false, false) if enemy => CommandType::Enqueue(Order::Attack(snowflakes.get(ray_cast.entity).copied().unwrap_or_default(), Some(ray_cast.entity))),
+                    (true, false, false) => CommandType::Enqueue(Order::Move(point)),
+                    (false, true, _) => CommandType::Patrol(point),
+                    (false, false, _) => CommandType::AttackMove(point),
+                };
+                unit_commands.write(CommandEvent {
+                    player: player.0,
+                    objects: units.iter().filter_map(|(id, sel)| if sel.selected { Some(id) } else { None }).collect(),
+                    command,
+                });
+            }
+            return;
+        }
         if input.just_released(MouseButton::Right) {
             if let Some(ray_cast) = cast.current_cast {
                 if combat_world.is_enemy(ray_cast.entity, player.0, &team_players)
@@ -786,11 +867,21 @@ impl CameraPlugin {
                         command: CommandType::Attack(ray_cast.entity),
                     };
                     unit_commands.write(command);
+                } else if garrisons.get(ray_cast.entity).map_or(false, |(_, _, garrison)| garrison.capacity > 0)
+                    && team_players.get(ray_cast.entity).map_or(false, |teamplayer| *teamplayer == player.0) {
+                    unit_commands.write(CommandEvent {
+                        player: player.0,
+                        objects: units.iter().filter_map(|(id, sel)| if sel.selected { Some(id) } else { None }).collect(),
+                        command: CommandType::Garrison(ray_cast.entity),
+                    });
                 } else {
+                    let point = ray_cast.point.xz();
+                    let destination = drag_start.unwrap_or(point);
+                    let facing = (destination.distance(point) > FORMATION_DRAG_THRESHOLD).then(|| point - destination);
                     unit_commands.write(CommandEvent {
                         player: player.0,
                         objects: units.iter().filter_map(|(id, sel)| if sel.selected { Some(id) } else { None }).collect(),
-                        command: CommandType::Move(ray_cast.point.xz()),
+                        command: CommandType::Formation(Formation { destination, facing, shape: formation.0 }),
                     });
                 }
             }
@@ -897,6 +988,7 @@ impl Plugin for CameraPlugin {
         app
             .add_event::<SelectionEvent>()
             .insert_resource(CurrentPlacement::<CLICK_BUFFER>::new())
+            .init_resource::<SelectedFormation>()
             .add_systems(OnEnter(GameState::SingleplayerGame), (
                 Self::create_camera,
                 Self::create_selector,
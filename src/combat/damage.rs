@@ -0,0 +1,71 @@
+use std::{fs::File, io::{BufWriter, Write}, path::Path};
+use bevy::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::*;
+
+///Counts combat frames so damage can be ordered and grouped after the fact.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Resource)]
+pub struct CombatTick(pub u64);
+
+///Damage that landed on an object.
+#[derive(Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize)]
+#[derive(Event)]
+pub struct DamageEvent {
+    ///[None] for damage over time from status effects.
+    pub attacker: Option<Entity>,
+    pub victim: Entity,
+    ///Index into the attacker's [`WeaponSet`].
+    pub weapon: Option<usize>,
+    ///Damage per channel before resistances.
+    pub raw: DamageTypes,
+    ///Damage per channel after resistances.
+    pub mitigated: DamageTypes,
+    pub tick: u64,
+}
+
+///Writes every [`DamageEvent`] to a file, one JSON object per line.
+#[derive(Debug)]
+#[derive(Resource)]
+pub struct DamageRecorder {
+    writer: BufWriter<File>,
+}
+
+impl DamageRecorder {
+    pub fn create<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+        })
+    }
+
+    pub fn record(&mut self, event: &DamageEvent) -> std::io::Result<()> {
+        serde_json::to_writer(&mut self.writer, event)?;
+        self.writer.write_all(b"\n")
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl CombatPlugin {
+    pub fn combat_tick(
+        mut tick: ResMut<CombatTick>,
+    ) {
+        tick.0 += 1;
+    }
+
+    pub fn record_damage(
+        mut recorder: ResMut<DamageRecorder>,
+        mut damage_events: EventReader<DamageEvent>,
+        mut commands: Commands,
+    ) {
+        if damage_events.is_empty() { return; }
+        let result = damage_events.read().try_for_each(|event| recorder.record(event)).and_then(|_| recorder.flush());
+        if let Err(e) = result {
+            error!("Damage recorder stopped: {}", e);
+            commands.remove_resource::<DamageRecorder>();
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use avian3d::prelude::LinearVelocity;
+use serde::{Serialize, Deserialize};
+use crate::*;
+
+///The wreck an object leaves behind when it finishes dying.
+#[derive(Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize)]
+pub struct WreckData {
+    ///Grid cells blocked either side of the wreck's center.
+    pub half_extents: (isize, isize),
+    ///Seconds before the wreck is cleared. [None] keeps it for the rest of the match.
+    pub lifetime: Option<f32>,
+}
+
+///How an object leaves the world once its health runs out.
+#[derive(Debug, Default, Clone, Copy)]
+#[derive(Serialize, Deserialize)]
+#[derive(Component)]
+pub struct Death {
+    ///Seconds the object stays in the world after dying.
+    pub duration: f32,
+    pub wreck: Option<WreckData>,
+}
+
+///Marks an object whose health has run out. It no longer acts, can't be targeted, and is despawned once `remaining` reaches zero.
+#[derive(Debug, Clone, Copy)]
+#[derive(Component)]
+pub struct Dying {
+    pub remaining: f32,
+    pub killer: Option<Entity>,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[derive(Component)]
+pub struct Wreck {
+    pub half_extents: (isize, isize),
+    pub remaining: Option<f32>,
+}
+
+///Sent just before a dying object is despawned, with what listeners need now that it's gone.
+#[derive(Debug, Clone, Copy)]
+#[derive(Event)]
+pub struct ObjectRemovedEvent {
+    pub entity: Entity,
+    pub object_type: Option<ObjectType>,
+    pub transform: Transform,
+}
+
+impl CombatPlugin {
+    pub fn dying_system(
+        time: Res<Time>,
+        gltf_assets: Option<Res<GltfAssets>>,
+        mut removed_writer: EventWriter<ObjectRemovedEvent>,
+        mut dying: Query<(Entity, &mut Dying, &Transform, Option<&Death>, Option<&ObjectType>, Option<&mut LinearVelocity>)>,
+        mut commands: Commands,
+    ) {
+        dying.iter_mut().for_each(|(entity, mut dying, transform, death, object_type, velocity)| {
+            if let Some(mut velocity) = velocity {
+                velocity.0 = Vec3::ZERO;
+            }
+            dying.remaining -= time.delta_secs();
+            if dying.remaining > 0.0 { return; }
+
+            if let Some(wreck) = death.and_then(|death| death.wreck) {
+                let mut wreck_commands = commands.spawn((
+                    Wreck {
+                        half_extents: wreck.half_extents,
+                        remaining: wreck.lifetime,
+                    },
+                    *transform,
+                    Visibility::default(),
+                ));
+                if let Some(scene) = gltf_assets.as_ref().zip(object_type).and_then(|(gltf_assets, object_type)| gltf_assets.get_object(*object_type)) {
+                    wreck_commands.with_children(|parent| {
+                        parent.spawn(SceneRoot(scene.clone()));
+                    });
+                }
+            }
+            removed_writer.write(ObjectRemovedEvent {
+                entity,
+                object_type: object_type.copied(),
+                transform: *transform,
+            });
+            if let Ok(mut entity_commands) = commands.get_entity(entity) {
+                entity_commands.despawn();
+            }
+        });
+    }
+}
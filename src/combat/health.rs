@@ -1,4 +1,4 @@
-use bevy::prelude::Component;
+use bevy::prelude::{Component, Entity};
 use serde::{Serialize, Deserialize};
 use crate::*;
 
@@ -13,6 +13,8 @@ pub struct Health {
     resistance: f32,
     resistances: DamageTypes,
     dense: bool,
+    #[serde(skip)]
+    last_attacker: Option<Entity>,
 }
 
 impl Health {
@@ -23,6 +25,7 @@ impl Health {
             resistance,
             resistances,
             dense,
+            last_attacker: None,
         }
     }
 
@@ -34,6 +37,18 @@ impl Health {
         self.health
     }
 
+    ///Changes max health, keeping the same health percent.
+    pub fn set_max_health(&mut self, max_health: f32) {
+        let percent = self.health_percent();
+        self.max_health = max_health;
+        self.health = max_health * percent;
+    }
+
+    ///Brings health back to max.
+    pub fn restore(&mut self) {
+        self.health = self.max_health;
+    }
+
     pub fn health_percent(&self) -> f32 {
         self.health / self.max_health
     }
@@ -46,12 +61,39 @@ impl Health {
         !self.is_full_health()
     }
 
-    pub fn damage(&mut self, damage: f32, dmg_types: DamageTypes) {
-        self.health -= (damage * dmg_types.kinetic - (damage * dmg_types.kinetic * (self.resistances.kinetic + self.resistance).clamp(MIN_VALUE, MAX_VALUE)))
-            + (damage * dmg_types.fire - (damage * dmg_types.fire * (self.resistances.fire + self.resistance).clamp(MIN_VALUE, MAX_VALUE)))
-            + (damage * dmg_types.explosive - (damage * dmg_types.explosive * (self.resistances.explosive + self.resistance).clamp(MIN_VALUE, MAX_VALUE)))
-            + (damage * dmg_types.laser - (damage * dmg_types.laser * (self.resistances.laser + self.resistance).clamp(MIN_VALUE, MAX_VALUE)))
-            + (damage * dmg_types.shock - (damage * dmg_types.shock * (self.resistances.shock + self.resistance).clamp(MIN_VALUE, MAX_VALUE)))
+    ///The damage per channel this object would take after resistances.
+    pub fn mitigate(&self, damage: f32, dmg_types: DamageTypes) -> DamageTypes {
+        DamageTypes {
+            kinetic: damage * dmg_types.kinetic - (damage * dmg_types.kinetic * (self.resistances.kinetic + self.resistance).clamp(MIN_VALUE, MAX_VALUE)),
+            fire: damage * dmg_types.fire - (damage * dmg_types.fire * (self.resistances.fire + self.resistance).clamp(MIN_VALUE, MAX_VALUE)),
+            explosive: damage * dmg_types.explosive - (damage * dmg_types.explosive * (self.resistances.explosive + self.resistance).clamp(MIN_VALUE, MAX_VALUE)),
+            laser: damage * dmg_types.laser - (damage * dmg_types.laser * (self.resistances.laser + self.resistance).clamp(MIN_VALUE, MAX_VALUE)),
+            shock: damage * dmg_types.shock - (damage * dmg_types.shock * (self.resistances.shock + self.resistance).clamp(MIN_VALUE, MAX_VALUE)),
+        }
+    }
+
+    ///Returns how much health was actually lost.
+    pub fn damage(&mut self, damage: f32, dmg_types: DamageTypes) -> f32 {
+        let before = self.health.max(0.0);
+        self.health -= self.mitigate(damage, dmg_types).total();
+        (before - self.health.max(0.0)).max(0.0)
+    }
+
+    ///Damages this object, crediting `attacker` if it dies.
+    pub fn damage_from(&mut self, attacker: Entity, damage: f32, dmg_types: DamageTypes) -> f32 {
+        self.last_attacker = Some(attacker);
+        self.damage(damage, dmg_types)
+    }
+
+    pub fn last_attacker(&self) -> Option<Entity> {
+        self.last_attacker
+    }
+
+    ///Returns how much health was actually restored.
+    pub fn heal(&mut self, amount: f32) -> f32 {
+        let before = self.health;
+        self.health = (self.health + amount.max(0.0)).min(self.max_health);
+        self.health - before
     }
 
     pub fn is_alive(&self) -> bool {
@@ -1,16 +1,27 @@
+pub mod damage;
+pub mod death;
 pub mod health;
+pub mod status;
+pub mod turret;
+pub mod veterancy;
 pub mod weapon;
 
+pub use damage::*;
+pub use death::*;
 pub use health::*;
+pub use status::*;
+pub use turret::*;
+pub use veterancy::*;
 pub use weapon::*;
 
 use bevy::{math::Vec3Swizzles, platform::collections::HashMap, prelude::*};
 use xtrees::{Quad, QuadTree};
 use crate::*;
 
+///The object that was killed, and the object credited with the kill if there is one.
 #[derive(Debug, Clone, Copy)]
 #[derive(Event)]
-pub struct ObjectKilledEvent(pub Entity);
+pub struct ObjectKilledEvent(pub Entity, pub Option<Entity>);
 
 
 #[derive(Debug, Default, Clone)]
@@ -89,7 +100,7 @@ impl CombatWorld {
         }
     }
 
-    pub fn search_targets(&self, id: TeamPlayer, position: Vec3, weapon: &Weapon) -> Vec<Entity> {
+    pub fn search_targets(&self, id: TeamPlayer, position: Vec3, facing: Vec2, weapon: &Weapon) -> Vec<Entity> {
         let pos = position.xz();
 
         match weapon.target_force {
@@ -97,7 +108,15 @@ impl CombatWorld {
             TargetForce::Ally => { self.search_allies(id, pos, weapon.range) },
             TargetForce::Team => { self.search_mine_or_allies(id, pos, weapon.range) },
             TargetForce::Enemy => { self.search_enemies(id, pos, weapon.range) },
-        }.iter().filter_map(|(e, target_pos)| (pos.distance(*target_pos) <= weapon.range).then_some(*e) ).collect()
+        }.iter().filter_map(|(e, target_pos)| weapon.can_engage(pos, facing, *target_pos).then_some(*e) ).collect()
+    }
+
+    ///Everything within range regardless of who owns it.
+    pub fn search_nearby(&self, position: Vec2, range: f32) -> Vec<(Entity, Vec2)> {
+        self.layers.values()
+            .map(|tree| {
+                tree.search(&Quad::new(position.x, position.y, range, range)).iter().map(|(entity, quad)| (*entity, Vec2::new(quad.x, quad.y))).collect::<Vec<_>>()
+            }).flatten().collect()
     }
 
     fn search_mine(&self, id: TeamPlayer, position: Vec2, range: f32) -> Vec<(Entity, Vec2)> {
@@ -124,7 +143,7 @@ impl CombatWorld {
             }).flatten().collect()
     }
 
-    fn search_enemies(&self, id: TeamPlayer, position: Vec2, range: f32) -> Vec<(Entity, Vec2)> {
+    pub fn search_enemies(&self, id: TeamPlayer, position: Vec2, range: f32) -> Vec<(Entity, Vec2)> {
         self.layers.iter()
             .filter(|(_id, _)| _id.team() != id.team())
             .map(|(_id, tree)| {
@@ -139,24 +158,31 @@ pub struct CombatPlugin;
 impl CombatPlugin {
     fn targeting_system(
         teamplayer_world: Res<CombatWorld>,
-        transforms: Query<&Transform>,
-        mut query: Query<(&Transform, &mut PathFinder, &mut Navigator, &mut WeaponSet, &TeamPlayer,)>,
+        transforms: Query<&Transform, (Without<Dying>, Without<Garrisoned>)>,
+        healths: Query<&Health>,
+        mut query: Query<(Entity, &Transform, &mut PathFinder, &mut Navigator, &mut WeaponSet, &TeamPlayer, Option<&mut Stance>), Without<Dying>>,
     ) {
         //TODO: Make sure weapons can only target the target if they are able to.
-        query.iter_mut().for_each(|(transform, mut pathfinder, mut navigator, mut weapon_set, teamplayer)| {
+        query.iter_mut().for_each(|(entity, transform, mut pathfinder, mut navigator, mut weapon_set, teamplayer, mut stance)| {
             match navigator.pursue {
                 Some(target) => {
                     if let Ok(target_transform) = transforms.get(target) {
                         let pos = transform.translation.xz();
                         let target_pos = target_transform.translation.xz();
+                        let min_range = weapon_set.min_range().unwrap_or(0.0);
                         if pos.distance(target_pos) > weapon_set.closing_range {
                             let start = transform.translation.xz();
                             let end = target_transform.translation.xz() + (pos - target_pos).normalize() * weapon_set.closing_range;
                             pathfinder.set_trip((start, end));
+                        } else if pos.distance(target_pos) < min_range && !pathfinder.reversing() {
+                            let end = target_pos + (pos - target_pos).normalize_or_zero() * (min_range + weapon_set.closing_range) * 0.5;
+                            pathfinder.set_reverse_trip((pos, end));
                         }
 
                         for weapon in weapon_set.weapons.iter_mut() {
-                            if pos.distance(target_pos) > weapon.range {
+                            if pos.distance(target_pos) < weapon.min_range {
+                                weapon.target = Target::None;
+                            } else if pos.distance(target_pos) > weapon.range && !weapon.heals {
                                 weapon.target = Target::ManualTarget(target);
                             } else if let Target::AutoTarget(_) = weapon.target {
 
@@ -169,62 +195,233 @@ impl CombatPlugin {
                     }
                 },
                 None => {
+                    let pos = transform.translation.xz();
+                    let facing = (transform.rotation * -Vec3::Z).xz();
+                    let idle = pathfinder.trip().is_none() && pathfinder.path().is_none();
+                    if let Some(stance) = stance.as_mut() {
+                        if stance.post.is_none() && idle {
+                            stance.post = Some(pos);
+                        }
+                    }
+                    let current = stance.as_deref().copied().unwrap_or_default();
+                    let post = current.post.unwrap_or(pos);
+                    let chase_distance = current.chase_distance();
+                    let closing_range = weapon_set.closing_range;
+                    let mut chase = None;
+
                     for weapon in weapon_set.weapons.iter_mut() {
                         if let Target::ManualTarget(_) = weapon.target {
                             weapon.target = Target::None;
                         } else if let Target::AutoTarget(target) = weapon.target {
-                            if let Ok(target_transform) = transforms.get(target) {
-                                if transform.translation.xz().distance(target_transform.translation.xz()) > weapon.range {
+                            if weapon.heals && healths.get(target).map_or(true, |health| health.is_full_health()) {
+                                weapon.target = Target::None;
+                            } else if let Ok(target_transform) = transforms.get(target) {
+                                let target_pos = target_transform.translation.xz();
+                                if pos.distance(target_pos) < weapon.min_range {
                                     weapon.target = Target::None;
+                                } else if pos.distance(target_pos) > weapon.range {
+                                    match chase_distance {
+                                        Some(leash) if post.distance(target_pos) - closing_range <= leash => {
+                                            chase = chase.or(Some(target_pos));
+                                        },
+                                        _ => {
+                                            weapon.target = Target::None;
+                                        }
+                                    }
                                 }
                             } else {
                                 weapon.target = Target::None;
                             }
                         }
                         if let Target::None = weapon.target {
-                            let targets = teamplayer_world.search_targets(*teamplayer, transform.translation, weapon);
-                            if let Some(e) = targets.first() {
-                                weapon.target = Target::AutoTarget(*e);
+                            if !current.auto_targets() {
+                                continue;
+                            }
+                            let target = Self::prioritize(entity, weapon, teamplayer_world.search_targets(*teamplayer, transform.translation, facing, weapon), &healths);
+                            if let Some(e) = target {
+                                weapon.target = Target::AutoTarget(e);
+                            } else if let Some(leash) = chase_distance.filter(|_| idle || current.chasing) {
+                                let mut sight = *weapon;
+                                sight.range = leash;
+                                sight.arc = None;
+                                let target = Self::prioritize(entity, weapon, teamplayer_world.search_targets(*teamplayer, Vec3::new(post.x, 0.0, post.y), facing, &sight), &healths);
+                                if let Some(e) = target {
+                                    weapon.target = Target::AutoTarget(e);
+                                }
                             }
                         }
                     }
+
+                    let Some(stance) = stance.as_mut() else { return; };
+                    match chase {
+                        Some(target_pos) if idle || stance.chasing => {
+                            let end = target_pos + (pos - target_pos).normalize_or_zero() * closing_range;
+                            let stale = pathfinder.path().as_ref().and_then(|path| path.last()).map_or(true, |last| last.distance(end) > closing_range * 0.5);
+                            if stale && pathfinder.trip().is_none() {
+                                pathfinder.set_trip((pos, end));
+                            }
+                            stance.chasing = true;
+                        },
+                        _ if stance.chasing => {
+                            stance.chasing = false;
+                            if stance.returns_to_post() && pos.distance(post) > closing_range * 0.5 {
+                                pathfinder.set_trip((pos, post));
+                            }
+                        },
+                        _ => { }
+                    }
+                }
+            }
+        });
+    }
+
+    ///Targeting for objects that can't move, such as defenses. They keep engaging a target while it stays in reach and otherwise pick a new one.
+    fn static_targeting_system(
+        teamplayer_world: Res<CombatWorld>,
+        transforms: Query<&Transform, (Without<Dying>, Without<Garrisoned>)>,
+        healths: Query<&Health>,
+        mut query: Query<(Entity, &Transform, &mut WeaponSet, &TeamPlayer), (Without<PathFinder>, Without<Dying>)>,
+    ) {
+        query.iter_mut().for_each(|(entity, transform, mut weapon_set, teamplayer)| {
+            let pos = transform.translation.xz();
+            let facing = (transform.rotation * -Vec3::Z).xz();
+            for weapon in weapon_set.weapons.iter_mut() {
+                if let Some(target) = weapon.target.get_target() {
+                    let engageable = transforms.get(target).map_or(false, |target_transform| weapon.can_engage(pos, facing, target_transform.translation.xz()));
+                    let healed = weapon.heals && healths.get(target).map_or(true, |health| health.is_full_health());
+                    if engageable && !healed {
+                        continue;
+                    }
+                    weapon.target = Target::None;
+                }
+                if let Some(e) = Self::prioritize(entity, weapon, teamplayer_world.search_targets(*teamplayer, transform.translation, facing, weapon), &healths) {
+                    weapon.target = Target::AutoTarget(e);
                 }
             }
         });
     }
 
+    ///Picks which of the found targets a weapon should engage. Healing weapons go for the most damaged object other than themselves.
+    fn prioritize(entity: Entity, weapon: &Weapon, targets: Vec<Entity>, healths: &Query<&Health>) -> Option<Entity> {
+        if !weapon.heals {
+            return targets.first().copied();
+        }
+        targets.into_iter()
+            .filter(|target| *target != entity)
+            .filter_map(|target| healths.get(target).ok().filter(|health| health.is_not_full_health()).map(|health| (target, health.health_percent())))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(target, _)| target)
+    }
+
     fn weapons_system(
         time: Res<Time>,
-        mut weapons: Query<&mut WeaponSet>,
-        mut healths: Query<&mut Health>
+        tick: Res<CombatTick>,
+        mut damage_events: EventWriter<DamageEvent>,
+        mut weapons: Query<(Entity, &mut WeaponSet, Option<&mut Veterancy>, Option<&Squad>, Option<&Turrets>), Without<Dying>>,
+        mut healths: Query<&mut Health>,
+        mut status_effects: Query<&mut StatusEffects>,
+        teams: Query<&TeamPlayer>,
     ) {
-        weapons.iter_mut().for_each(|mut wep| {
-            for weapon in wep.weapons.iter_mut() {
+        weapons.iter_mut().for_each(|(entity, mut wep, mut veterancy, squad, turrets)| {
+            let shocked = status_effects.get(entity).map_or(false, |effects| effects.is_shocked());
+            let damage_bonus = veterancy.as_deref().map_or(1.0, |veterancy| veterancy.damage_bonus()) * squad.map_or(1.0, |squad| squad.strength());
+            let fire_rate_bonus = veterancy.as_deref().map_or(1.0, |veterancy| veterancy.fire_rate_bonus());
+            for (index, weapon) in wep.weapons.iter_mut().enumerate() {
                 if weapon.cooldown > 0.0 {
                     weapon.cooldown -= time.delta_secs();
                 }
-                if weapon.cooldown > 0.0 {
+                if weapon.cooldown > 0.0 || shocked || !turrets.map_or(true, |turrets| turrets.aligned(index)) {
                     continue;
                 }
-                if let Some(mut health) = weapon.target.get_target().and_then(|target| healths.get_mut(target).ok()) {
-                    health.damage(weapon.damage, weapon.damage_types);
-                    weapon.cooldown = weapon.fire_rate;
+                let Some(target) = weapon.target.get_target() else { continue; };
+                if let Ok(mut health) = healths.get_mut(target) {
+                    if weapon.heals {
+                        let allied = teams.get(entity).ok().zip(teams.get(target).ok()).map_or(false, |(a, b)| a.team() == b.team());
+                        if !allied {
+                            weapon.target = Target::None;
+                            continue;
+                        }
+                        health.heal(weapon.damage * damage_bonus);
+                        weapon.cooldown = weapon.fire_rate / fire_rate_bonus;
+                        continue;
+                    }
+                    let damage = weapon.damage * damage_bonus;
+                    damage_events.write(DamageEvent {
+                        attacker: Some(entity),
+                        victim: target,
+                        weapon: Some(index),
+                        raw: weapon.damage_types.scaled(damage),
+                        mitigated: health.mitigate(damage, weapon.damage_types),
+                        tick: tick.0,
+                    });
+                    let dealt = health.damage_from(entity, damage, weapon.damage_types);
+                    if let Some(veterancy) = veterancy.as_mut() {
+                        veterancy.damage_dealt(dealt);
+                    }
+                    weapon.cooldown = weapon.fire_rate / fire_rate_bonus;
+                    if let Ok(mut effects) = status_effects.get_mut(target) {
+                        if let Some(burn) = weapon.burn { effects.burn(burn); }
+                        if let Some(shock) = weapon.shock { effects.shock(shock); }
+                    }
                 }
             }
         });
     }
 
+    fn status_effects_system(
+        time: Res<Time>,
+        tick: Res<CombatTick>,
+        mut damage_events: EventWriter<DamageEvent>,
+        mut query: Query<(Entity, &mut StatusEffects, &mut Health), Without<Dying>>,
+    ) {
+        query.iter_mut().for_each(|(entity, mut effects, mut health)| {
+            if effects.is_empty() { return; }
+            if effects.is_burning() {
+                let damage = effects.burn_damage() * time.delta_secs();
+                damage_events.write(DamageEvent {
+                    attacker: None,
+                    victim: entity,
+                    weapon: None,
+                    raw: DamageTypes::fire().scaled(damage),
+                    mitigated: health.mitigate(damage, DamageTypes::fire()),
+                    tick: tick.0,
+                });
+                health.damage(damage, DamageTypes::fire());
+            }
+            effects.tick(time.delta_secs());
+        });
+    }
+
     fn health_system(
         mut objects_killed_writer: EventWriter<ObjectKilledEvent>,
-        query: Query<(Entity, &Health)>,
+        query: Query<(Entity, &Health, Option<&Death>), Without<Dying>>,
         mut commands: Commands,
     ) {
-        query.iter().for_each(|(entity, health)| {
+        query.iter().for_each(|(entity, health, death)| {
             if health.is_dead() {
                 if let Ok(mut entity_commands) = commands.get_entity(entity) {
-                    entity_commands.despawn();
+                    entity_commands.insert(Dying {
+                        remaining: death.map_or(0.0, |death| death.duration),
+                        killer: health.last_attacker(),
+                    }).remove::<Selectable>();
                 }
-                objects_killed_writer.write(ObjectKilledEvent(entity));
+                objects_killed_writer.write(ObjectKilledEvent(entity, health.last_attacker()));
+            }
+        });
+    }
+
+    fn veterancy_system(
+        mut kills: EventReader<ObjectKilledEvent>,
+        mut veterans: Query<(&mut Veterancy, &mut Health)>,
+    ) {
+        for ObjectKilledEvent(_, killer) in kills.read() {
+            let Some((mut veterancy, _)) = killer.and_then(|killer| veterans.get_mut(killer).ok()) else { continue; };
+            veterancy.killed();
+        }
+
+        veterans.iter_mut().for_each(|(mut veterancy, mut health)| {
+            if veterancy.update_rank() {
+                health.set_max_health(veterancy.max_health());
             }
         });
     }
@@ -237,11 +434,22 @@ impl Plugin for CombatPlugin {
     fn build(&self, app: &mut App) {
 
         app
+            .init_resource::<CombatTick>()
+            .add_event::<DamageEvent>()
+            .add_event::<ObjectRemovedEvent>()
             .add_systems(Update, (
+                Self::combat_tick.before(Self::targeting_system),
                 Self::targeting_system.after(CommandSystems),
-                Self::weapons_system.after(Self::targeting_system),
-                Self::health_system.after(Self::weapons_system),
+                Self::static_targeting_system.after(CommandSystems),
+                Self::aim_turrets.after(Self::targeting_system).after(Self::static_targeting_system),
+                Self::weapons_system.after(Self::aim_turrets),
+                Self::status_effects_system.after(Self::weapons_system),
+                Self::health_system.after(Self::status_effects_system),
+                Self::veterancy_system.after(Self::health_system),
+                Self::dying_system.after(Self::veterancy_system),
             ).in_set(CombatSystems))
+            .add_systems(Update, Self::spawn_turrets)
+            .add_systems(Update, Self::record_damage.after(CombatSystems).run_if(resource_exists::<DamageRecorder>))
         ;
     }
 }
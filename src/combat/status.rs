@@ -0,0 +1,105 @@
+use bevy::prelude::Component;
+use serde::{Serialize, Deserialize};
+use crate::*;
+
+#[derive(Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub strength: f32,
+    pub remaining: f32,
+}
+
+impl From<StatusEffectData> for StatusEffect {
+    fn from(data: StatusEffectData) -> Self {
+        Self {
+            strength: data.strength,
+            remaining: data.duration,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+#[derive(Serialize, Deserialize)]
+#[derive(Component)]
+pub struct StatusEffects {
+    burns: Vec<StatusEffect>,
+    shocks: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn burn(&mut self, data: StatusEffectData) {
+        Self::apply(&mut self.burns, data);
+    }
+
+    pub fn shock(&mut self, data: StatusEffectData) {
+        Self::apply(&mut self.shocks, data);
+    }
+
+    fn apply(effects: &mut Vec<StatusEffect>, data: StatusEffectData) {
+        match data.stacking {
+            StackingRule::Refresh => {
+                if let Some(effect) = effects.first_mut() {
+                    effect.remaining = effect.remaining.max(data.duration);
+                    effect.strength = effect.strength.max(data.strength);
+                } else {
+                    effects.push(data.into());
+                }
+            },
+            StackingRule::Stack => {
+                if effects.len() < data.max_stacks.max(1) as usize {
+                    effects.push(data.into());
+                } else if let Some(effect) = effects.iter_mut().min_by(|a, b| a.remaining.total_cmp(&b.remaining)) {
+                    *effect = data.into();
+                }
+            },
+            StackingRule::Ignore => {
+                if effects.is_empty() {
+                    effects.push(data.into());
+                }
+            },
+        }
+    }
+
+    pub fn burns(&self) -> &Vec<StatusEffect> {
+        &self.burns
+    }
+
+    pub fn shocks(&self) -> &Vec<StatusEffect> {
+        &self.shocks
+    }
+
+    pub fn is_burning(&self) -> bool {
+        !self.burns.is_empty()
+    }
+
+    pub fn is_shocked(&self) -> bool {
+        !self.shocks.is_empty()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.is_burning() && !self.is_shocked()
+    }
+
+    ///Combined damage per second of every burn.
+    pub fn burn_damage(&self) -> f32 {
+        self.burns.iter().map(|burn| burn.strength).sum()
+    }
+
+    pub fn tick(&mut self, delta: f32) {
+        for effect in self.burns.iter_mut().chain(self.shocks.iter_mut()) {
+            effect.remaining -= delta;
+        }
+        self.burns.retain(|burn| burn.remaining > 0.0);
+        self.shocks.retain(|shock| shock.remaining > 0.0);
+    }
+}
+
+impl Slim for StatusEffects {
+    fn slim(&self) -> Option<Self> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.clone())
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::f32::consts::PI;
+use bevy::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::*;
+
+///A turret as declared in an object's RON file.
+#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct TurretData {
+    ///The model spawned for this turret.
+    pub object_type: ObjectType,
+    ///Where the turret sits on its parent when facing forward.
+    pub rest: Transform,
+    ///Indices into the parent's [`WeaponSet`] that this turret aims.
+    pub weapons: Vec<usize>,
+    ///Radians per second.
+    pub turn_rate: f32,
+    ///Minimum and maximum yaw in radians, relative to `rest`. [None] lets the turret spin freely.
+    pub yaw_limits: Option<(f32, f32)>,
+    ///How far in radians the turret may be from facing its target and still fire.
+    pub firing_cone: f32,
+}
+
+#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct Turret {
+    pub data: TurretData,
+    pub yaw: f32,
+    #[serde(skip)]
+    pub aligned: bool,
+    #[serde(skip)]
+    pub entity: Option<Entity>,
+}
+
+impl From<TurretData> for Turret {
+    fn from(data: TurretData) -> Self {
+        Self {
+            data,
+            yaw: 0.0,
+            aligned: false,
+            entity: None,
+        }
+    }
+}
+
+impl Turret {
+    pub fn transform(&self) -> Transform {
+        Transform {
+            rotation: Quat::from_rotation_y(self.yaw) * self.data.rest.rotation,
+            ..self.data.rest
+        }
+    }
+
+    ///How far `rest` is already turned about the parent's up axis.
+    fn rest_yaw(&self) -> f32 {
+        self.data.rest.rotation.to_euler(EulerRot::YXZ).0
+    }
+
+    fn clamp_yaw(&self, yaw: f32) -> f32 {
+        match self.data.yaw_limits {
+            Some((min, max)) => yaw.clamp(min, max),
+            None => yaw,
+        }
+    }
+
+    ///Turns toward `desired`, taking the short way around unless the turret has yaw limits.
+    pub fn turn_towards(&mut self, desired: f32, delta: f32) {
+        let desired = self.clamp_yaw(desired);
+        let difference = match self.data.yaw_limits {
+            Some(_) => desired - self.yaw,
+            None => wrap_angle(desired - self.yaw),
+        };
+        let step = self.data.turn_rate * delta;
+        self.yaw = if difference.abs() <= step { self.yaw + difference } else { self.yaw + step * difference.signum() };
+        if self.data.yaw_limits.is_none() {
+            self.yaw = wrap_angle(self.yaw);
+        }
+    }
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+#[derive(Debug, Default, Clone)]
+#[derive(Serialize, Deserialize)]
+#[derive(Component)]
+pub struct Turrets {
+    pub turrets: Vec<Turret>,
+}
+
+impl Turrets {
+    ///Whether the weapon at `index` may fire. Weapons not mounted on a turret can always fire.
+    pub fn aligned(&self, index: usize) -> bool {
+        let mut mounted = self.turrets.iter().filter(|turret| turret.data.weapons.contains(&index)).peekable();
+        mounted.peek().is_none() || mounted.any(|turret| turret.aligned)
+    }
+}
+
+impl From<Vec<TurretData>> for Turrets {
+    fn from(turrets: Vec<TurretData>) -> Self {
+        Self {
+            turrets: turrets.into_iter().map(|data| data.into()).collect(),
+        }
+    }
+}
+
+impl Slim for Turrets {
+    fn slim(&self) -> Option<Self> {
+        if self.turrets.iter().all(|turret| turret.yaw == 0.0) {
+            None
+        } else {
+            Some(self.clone())
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+#[derive(Component)]
+pub struct TurretMarker;
+
+impl CombatPlugin {
+    pub fn spawn_turrets(
+        mut objects: Query<(Entity, &mut Turrets), Added<Turrets>>,
+        mut commands: Commands,
+    ) {
+        objects.iter_mut().for_each(|(entity, mut turrets)| {
+            for turret in turrets.turrets.iter_mut() {
+                turret.entity = Some(commands.spawn((
+                    TurretMarker,
+                    turret.data.object_type,
+                    turret.transform(),
+                    Visibility::default(),
+                    ChildOf(entity),
+                )).id());
+            }
+        });
+    }
+
+    pub fn aim_turrets(
+        time: Res<Time>,
+        mut objects: Query<(&GlobalTransform, &mut Turrets, &WeaponSet)>,
+        global_transforms: Query<&GlobalTransform>,
+        mut transforms: Query<&mut Transform, With<TurretMarker>>,
+    ) {
+        objects.iter_mut().for_each(|(global_transform, mut turrets, weapon_set)| {
+            let inverse = global_transform.affine().inverse();
+            for turret in turrets.turrets.iter_mut() {
+                let target = turret.data.weapons.iter()
+                    .filter_map(|index| weapon_set.weapons.get(*index))
+                    .find_map(|weapon| weapon.target.get_target())
+                    .and_then(|target| global_transforms.get(target).ok());
+
+                match target {
+                    Some(target_transform) => {
+                        let direction = inverse.transform_point3(target_transform.translation()) - turret.data.rest.translation;
+                        let desired = wrap_angle((-direction.x).atan2(-direction.z) - turret.rest_yaw());
+                        turret.turn_towards(desired, time.delta_secs());
+                        turret.aligned = wrap_angle(desired - turret.yaw).abs() <= turret.data.firing_cone;
+                    },
+                    None => {
+                        turret.turn_towards(0.0, time.delta_secs());
+                        turret.aligned = false;
+                    }
+                }
+
+                let Some(mut transform) = turret.entity.and_then(|entity| transforms.get_mut(entity).ok()) else { continue; };
+                *transform = turret.transform();
+            }
+        });
+    }
+}
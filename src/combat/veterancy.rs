@@ -0,0 +1,110 @@
+use bevy::prelude::Component;
+use serde::{Serialize, Deserialize};
+use crate::*;
+
+///Bonuses a unit receives once it reaches `experience`.
+#[derive(Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize)]
+pub struct Rank {
+    pub experience: f32,
+    ///Multiplier on max health.
+    pub health: f32,
+    ///Multiplier on weapon damage.
+    pub damage: f32,
+    ///Multiplier on how often weapons fire.
+    pub fire_rate: f32,
+}
+
+#[derive(Debug, Default, Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct AssetVeterancy {
+    ///Experience earned per point of damage dealt.
+    pub damage_experience: f32,
+    ///Experience earned per kill.
+    pub kill_experience: f32,
+    ///Must be sorted by experience.
+    pub ranks: Vec<Rank>,
+}
+
+#[derive(Debug, Default, Clone)]
+#[derive(Component)]
+pub struct Veterancy {
+    damage_experience: f32,
+    kill_experience: f32,
+    ranks: Vec<Rank>,
+    base_health: f32,
+    experience: f32,
+    rank: usize,
+}
+
+impl Veterancy {
+    pub fn new(asset: AssetVeterancy, base_health: f32) -> Self {
+        Self {
+            damage_experience: asset.damage_experience,
+            kill_experience: asset.kill_experience,
+            ranks: asset.ranks,
+            base_health,
+            experience: 0.0,
+            rank: 0,
+        }
+    }
+
+    pub fn with_experience(mut self, experience: Option<f32>) -> Self {
+        if let Some(experience) = experience { self.experience = experience; }
+        self
+    }
+
+    pub fn experience(&self) -> f32 {
+        self.experience
+    }
+
+    pub fn set_experience(&mut self, experience: f32) {
+        self.experience = experience;
+    }
+
+    pub fn rank(&self) -> usize {
+        self.rank
+    }
+
+    pub fn damage_dealt(&mut self, damage: f32) {
+        self.experience += damage * self.damage_experience;
+    }
+
+    pub fn killed(&mut self) {
+        self.experience += self.kill_experience;
+    }
+
+    ///Updates the rank to match the current experience. Returns true if it changed.
+    pub fn update_rank(&mut self) -> bool {
+        let rank = self.ranks.iter().take_while(|rank| self.experience >= rank.experience).count();
+        let changed = rank != self.rank;
+        self.rank = rank;
+        changed
+    }
+
+    fn current(&self) -> Option<&Rank> {
+        self.rank.checked_sub(1).and_then(|rank| self.ranks.get(rank))
+    }
+
+    pub fn max_health(&self) -> f32 {
+        self.base_health * self.current().map_or(1.0, |rank| rank.health)
+    }
+
+    pub fn damage_bonus(&self) -> f32 {
+        self.current().map_or(1.0, |rank| rank.damage)
+    }
+
+    pub fn fire_rate_bonus(&self) -> f32 {
+        self.current().map_or(1.0, |rank| rank.fire_rate)
+    }
+}
+
+impl Slim for Veterancy {
+    fn slim(&self) -> Option<Self> {
+        if self.experience > 0.0 {
+            Some(self.clone())
+        } else {
+            None
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use bevy::prelude::{Component, Entity};
+use bevy::prelude::{Component, Entity, Vec2};
 use serde::{Serialize, Deserialize};
 
 use crate::Slim;
@@ -47,6 +47,66 @@ pub struct DamageTypes {
     pub shock: f32,
 }
 
+impl DamageTypes {
+    pub fn explosive() -> Self {
+        Self {
+            kinetic: 0.0,
+            fire: 0.0,
+            explosive: 1.0,
+            laser: 0.0,
+            shock: 0.0,
+        }
+    }
+
+    pub fn fire() -> Self {
+        Self {
+            kinetic: 0.0,
+            fire: 1.0,
+            explosive: 0.0,
+            laser: 0.0,
+            shock: 0.0,
+        }
+    }
+
+    ///Spreads `amount` across the channels by their weights.
+    pub fn scaled(&self, amount: f32) -> Self {
+        Self {
+            kinetic: self.kinetic * amount,
+            fire: self.fire * amount,
+            explosive: self.explosive * amount,
+            laser: self.laser * amount,
+            shock: self.shock * amount,
+        }
+    }
+
+    pub fn total(&self) -> f32 {
+        self.kinetic + self.fire + self.explosive + self.laser + self.shock
+    }
+}
+
+///How a status effect behaves when it is applied to a target that already has it.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub enum StackingRule {
+    ///Resets the duration of the existing effect, keeping the stronger of the two.
+    #[default]
+    Refresh,
+    ///Adds another instance, up to `max_stacks`. Once full, the instance closest to expiring is replaced.
+    Stack,
+    ///Does nothing while the target is already affected.
+    Ignore,
+}
+
+#[derive(Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize)]
+pub struct StatusEffectData {
+    pub duration: f32,
+    ///Damage per second for burns. Shocks disable the target regardless of strength.
+    pub strength: f32,
+    pub stacking: StackingRule,
+    pub max_stacks: u32,
+}
+
 #[derive(Debug, Clone, Copy)]
 #[derive(Serialize, Deserialize)]
 pub struct Weapon {
@@ -54,12 +114,36 @@ pub struct Weapon {
     pub target_force: TargetForce,
     pub target_type: TargetType,
     pub range: f32,
+    ///Targets closer than this can't be engaged.
+    #[serde(default)]
+    pub min_range: f32,
+    ///Half angle in radians either side of the object's facing this weapon can fire into. [None] fires in every direction.
+    pub arc: Option<f32>,
     pub damage: f32,
+    ///Restores `damage` health to the target instead of removing it, ignoring resistances.
+    #[serde(default)]
+    pub heals: bool,
     pub damage_types: DamageTypes,
+    pub burn: Option<StatusEffectData>,
+    pub shock: Option<StatusEffectData>,
     pub fire_rate: f32,
     // #[serde(skip)]
     pub cooldown: f32,
 }
+impl Weapon {
+    ///Whether a target at `target` sits between this weapon's minimum and maximum range and inside its firing arc.
+    pub fn can_engage(&self, position: Vec2, facing: Vec2, target: Vec2) -> bool {
+        let distance = position.distance(target);
+        if distance < self.min_range || distance > self.range {
+            return false;
+        }
+        match self.arc {
+            Some(arc) if distance > 0.0 => facing.angle_to(target - position).abs() <= arc,
+            _ => true,
+        }
+    }
+}
+
 
 #[derive(Debug, Clone)]
 #[derive(Serialize, Deserialize)]
@@ -74,8 +158,9 @@ impl WeaponSet {
     pub fn max_range(&self) -> Option<f32> {
         self.weapons.iter().fold(None, |m, w| Some(m.unwrap_or(0.0).max(w.range)))
     }
+    ///The closest distance at which any weapon can engage.
     pub fn min_range(&self) -> Option<f32> {
-        self.weapons.iter().fold(None, |m, w| Some(m.unwrap_or(f32::MAX).min(w.range)))
+        self.weapons.iter().fold(None, |m, w| Some(m.unwrap_or(f32::MAX).min(w.min_range)))
     }
     pub fn no_targets(&self) -> Option<bool> {
         self.weapons.iter().fold(None, |t, w| Some(t.unwrap_or(true) & (w.target == Target::None)))
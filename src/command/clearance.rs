@@ -0,0 +1,56 @@
+use bevy::{platform::collections::HashSet, prelude::*};
+use avian3d::prelude::Collider;
+use pathing::DS2Map;
+
+use crate::*;
+
+///The widest clearance that gets its own [`GridMap`] layer. Anything bigger paths as if it were this size.
+pub const MAX_CLEARANCE: u8 = 4;
+
+///Which clearance layer the grid debug gizmos draw on top of the blocked cells. Cycled with F4, 0 shows only the base grid.
+#[derive(Debug, Default, Clone, Copy)]
+#[derive(Resource)]
+pub struct GridLayerGizmos(pub u8);
+
+///How many cells a unit needs between its center and anything blocked, judged from its collider seen from above.
+pub fn clearance(collider: &Collider, transform: &Transform, space: &GridSpace) -> u8 {
+    let aabb = collider.aabb(Vec3::ZERO, Quat::IDENTITY);
+    let radius = (aabb.max.xz().max(-aabb.min.xz()) * transform.scale.xz()).max_element();
+    let cells = radius / space.scale.min_element().max(f32::EPSILON) - 0.5;
+    (cells.ceil().max(0.0) as u8).min(MAX_CLEARANCE)
+}
+
+impl GridMap {
+    ///A copy of the grid with every blocked cell grown by `clearance` cells, so paths through it leave that much room on
+    ///each side. Terrain costs carry over unchanged.
+    pub fn clearance_layer(&self, clearance: u8) -> GridMap {
+        let reach = clearance as isize;
+        let blocked = self.blocked();
+        let mut grown: HashSet<(isize, isize)> = HashSet::new();
+        for (x, y) in blocked.iter() {
+            for x_offset in -reach..=reach {
+                for y_offset in -reach..=reach {
+                    if x_offset * x_offset + y_offset * y_offset > reach * reach { continue; }
+                    grown.insert((x + x_offset, y + y_offset));
+                }
+            }
+        }
+        let mut map = DS2Map::new();
+        if !grown.is_empty() {
+            map.add_objects(grown.into_iter().collect());
+            map.precompute();
+        }
+        GridMap(map, self.1.clone())
+    }
+
+    ///The center of the free cell closest to a position, looking up to twice [`MAX_CLEARANCE`] cells away. Used to move the
+    ///ends of a trip out of the room a layer keeps clear around obstacles.
+    pub fn nearest_free(&self, space: &GridSpace, position: Vec2, blocked: &HashSet<(isize, isize)>) -> Option<Vec2> {
+        let cell = space.position_to_index(position);
+        let reach = MAX_CLEARANCE as isize * 2;
+        (-reach..=reach).flat_map(|x| (-reach..=reach).map(move |y| (cell.0 + x, cell.1 + y)))
+            .filter(|free| !blocked.contains(free))
+            .map(|free| space.index_to_position(free))
+            .min_by(|a, b| a.distance_squared(position).total_cmp(&b.distance_squared(position)))
+    }
+}
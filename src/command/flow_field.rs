@@ -0,0 +1,186 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+use bevy::{platform::collections::{HashMap, HashSet}, prelude::*};
+
+use crate::*;
+
+///Group moves with at least this many units share a [`FlowField`] instead of pathing one by one.
+pub const FLOW_FIELD_THRESHOLD: usize = 8;
+///How many cells a field extends past the units and goal it was built for.
+pub const FLOW_FIELD_MARGIN: isize = 32;
+///How many fields the pathfinding worker keeps around before starting over.
+pub const FLOW_FIELD_CACHE: usize = 16;
+
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+const NEIGHBOURS: [(isize, isize); 8] = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+///An integration field over a rectangle of the [`GridMap`]. Each cell holds the cost of reaching the goal from it.
+#[derive(Debug, Clone)]
+pub struct FlowField {
+    goal: (isize, isize),
+    min: (isize, isize),
+    width: usize,
+    height: usize,
+    costs: Vec<u32>,
+}
+
+impl FlowField {
+    ///The area a field needs to cover so every start can reach the goal, give or take a detour of [`FLOW_FIELD_MARGIN`].
+    pub fn bounds(goal: (isize, isize), starts: &[(isize, isize)]) -> ((isize, isize), (isize, isize)) {
+        let (min, max) = starts.iter().fold((goal, goal), |(min, max), start| {
+            ((min.0.min(start.0), min.1.min(start.1)), (max.0.max(start.0), max.1.max(start.1)))
+        });
+        ((min.0 - FLOW_FIELD_MARGIN, min.1 - FLOW_FIELD_MARGIN), (max.0 + FLOW_FIELD_MARGIN, max.1 + FLOW_FIELD_MARGIN))
+    }
+
+    pub fn goal(&self) -> (isize, isize) {
+        self.goal
+    }
+
+    pub fn contains(&self, (x, y): (isize, isize)) -> bool {
+        x >= self.min.0 && y >= self.min.1 && x < self.min.0 + self.width as isize && y < self.min.1 + self.height as isize
+    }
+
+    fn index(&self, (x, y): (isize, isize)) -> Option<usize> {
+        self.contains((x, y)).then(|| (y - self.min.1) as usize * self.width + (x - self.min.0) as usize)
+    }
+
+    pub fn cost(&self, cell: (isize, isize)) -> Option<u32> {
+        self.index(cell).map(|index| self.costs[index]).filter(|cost| *cost != u32::MAX)
+    }
+
+    ///The cheapest neighbour of a cell, or `None` at the goal or outside the reachable area.
+    pub fn next(&self, cell: (isize, isize)) -> Option<(isize, isize)> {
+        let cost = self.cost(cell)?;
+        NEIGHBOURS.iter()
+            .map(|(x, y)| (cell.0 + x, cell.1 + y))
+            .filter_map(|neighbour| self.cost(neighbour).map(|cost| (neighbour, cost)))
+            .filter(|(_, neighbour_cost)| *neighbour_cost < cost)
+            .min_by_key(|(_, neighbour_cost)| *neighbour_cost)
+            .map(|(neighbour, _)| neighbour)
+    }
+
+    ///Walks the field downhill from a cell until it reaches one that costs no more than `stop`, keeping only the cells where
+    ///the direction changes. A `stop` of 0 walks all the way to the goal.
+    pub fn trace(&self, start: (isize, isize), stop: u32) -> Option<Vec<(isize, isize)>> {
+        let mut cost = self.cost(start)?;
+        let mut nodes = vec![start];
+        let mut current = start;
+        let mut direction = None;
+        while cost > stop {
+            let Some(next) = self.next(current) else { break; };
+            let step = (next.0 - current.0, next.1 - current.1);
+            if direction.is_some_and(|direction| direction == step) {
+                nodes.pop();
+            }
+            nodes.push(next);
+            direction = Some(step);
+            current = next;
+            cost = self.cost(next)?;
+        }
+        (cost <= stop).then_some(nodes)
+    }
+}
+
+impl GridMap {
+    ///Builds a [`FlowField`] towards a goal with Dijkstra's algorithm. Diagonal moves may not cut the corners of blocked cells.
+    ///`blocked` is this grid's [`GridMap::blocked`] set.
+    pub fn flow_field(&self, goal: (isize, isize), (min, max): ((isize, isize), (isize, isize)), blocked: &HashSet<(isize, isize)>) -> FlowField {
+        let width = (max.0 - min.0 + 1).max(1) as usize;
+        let height = (max.1 - min.1 + 1).max(1) as usize;
+        let mut field = FlowField {
+            goal,
+            min,
+            width,
+            height,
+            costs: vec![u32::MAX; width * height],
+        };
+        let Some(goal_index) = field.index(goal) else { return field; };
+
+        let mut open = BinaryHeap::new();
+        field.costs[goal_index] = 0;
+        open.push(Reverse((0, goal)));
+        while let Some(Reverse((cost, cell))) = open.pop() {
+            if field.index(cell).map_or(true, |index| field.costs[index] < cost) {
+                continue;
+            }
+            for (x, y) in NEIGHBOURS {
+                let neighbour = (cell.0 + x, cell.1 + y);
+                let Some(index) = field.index(neighbour) else { continue; };
+                if blocked.contains(&neighbour) {
+                    continue;
+                }
+                let diagonal = x != 0 && y != 0;
+                if diagonal && (blocked.contains(&(cell.0 + x, cell.1)) || blocked.contains(&(cell.0, cell.1 + y))) {
+                    continue;
+                }
+                let step = if diagonal { DIAGONAL_COST } else { STRAIGHT_COST };
+                let next_cost = cost + (step as f32 * self.1.cost(neighbour)).round() as u32;
+                if next_cost < field.costs[index] {
+                    field.costs[index] = next_cost;
+                    open.push(Reverse((next_cost, neighbour)));
+                }
+            }
+        }
+        field
+    }
+
+    ///Finds the cheapest path between two cells with A*, weighing steps by terrain cost the same way
+    ///[`GridMap::flow_field`] does. Only searches within the bounds and keeps only the cells where the direction changes.
+    pub fn weighted_path(&self, start: (isize, isize), goal: (isize, isize), (min, max): ((isize, isize), (isize, isize)), blocked: &HashSet<(isize, isize)>) -> Option<Vec<(isize, isize)>> {
+        let inside = |(x, y): (isize, isize)| x >= min.0 && y >= min.1 && x <= max.0 && y <= max.1;
+        if !inside(start) || !inside(goal) || blocked.contains(&goal) {
+            return None;
+        }
+        let heuristic = |(x, y): (isize, isize)| {
+            let (dx, dy) = ((x - goal.0).unsigned_abs() as u32, (y - goal.1).unsigned_abs() as u32);
+            DIAGONAL_COST * dx.min(dy) + STRAIGHT_COST * (dx.max(dy) - dx.min(dy))
+        };
+
+        let mut costs: HashMap<(isize, isize), u32> = HashMap::new();
+        let mut came_from: HashMap<(isize, isize), (isize, isize)> = HashMap::new();
+        let mut open = BinaryHeap::new();
+        costs.insert(start, 0);
+        open.push(Reverse((heuristic(start), start)));
+        while let Some(Reverse((estimate, cell))) = open.pop() {
+            let cost = costs[&cell];
+            if estimate > cost + heuristic(cell) {
+                continue;
+            }
+            if cell == goal {
+                let mut nodes = vec![goal];
+                let mut direction = None;
+                let mut current = goal;
+                while let Some(previous) = came_from.get(&current).copied() {
+                    let step = (current.0 - previous.0, current.1 - previous.1);
+                    if direction.is_some_and(|direction| direction == step) {
+                        nodes.pop();
+                    }
+                    nodes.push(previous);
+                    direction = Some(step);
+                    current = previous;
+                }
+                nodes.reverse();
+                return Some(nodes);
+            }
+            for (x, y) in NEIGHBOURS {
+                let neighbour = (cell.0 + x, cell.1 + y);
+                if !inside(neighbour) || blocked.contains(&neighbour) {
+                    continue;
+                }
+                let diagonal = x != 0 && y != 0;
+                if diagonal && (blocked.contains(&(cell.0 + x, cell.1)) || blocked.contains(&(cell.0, cell.1 + y))) {
+                    continue;
+                }
+                let step = if diagonal { DIAGONAL_COST } else { STRAIGHT_COST };
+                let next_cost = cost + (step as f32 * self.1.cost(neighbour)).round() as u32;
+                if costs.get(&neighbour).map_or(true, |known| next_cost < *known) {
+                    costs.insert(neighbour, next_cost);
+                    came_from.insert(neighbour, cell);
+                    open.push(Reverse((next_cost + heuristic(neighbour), neighbour)));
+                }
+            }
+        }
+        None
+    }
+}
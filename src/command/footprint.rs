@@ -0,0 +1,58 @@
+use bevy::{platform::collections::HashMap, prelude::*};
+use avian3d::prelude::Collider;
+use serde::{Serialize, Deserialize};
+
+use crate::*;
+
+///Which grid cells a structure blocks for pathfinding.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub enum Footprint {
+    ///Whatever the object's collider covers, seen from above.
+    #[default]
+    Collider,
+    ///A rectangle this far out from the object's center along its own x and z axes.
+    HalfExtents(f32, f32),
+    ///Nothing.
+    Clear,
+}
+
+impl Footprint {
+    ///The cells under the footprint once it is rotated and placed by `transform`.
+    pub fn cells(&self, transform: &Transform, collider: &Collider, space: &GridSpace) -> Vec<(isize, isize)> {
+        let (min, max) = match *self {
+            Footprint::Collider => {
+                let aabb = collider.aabb(Vec3::ZERO, Quat::IDENTITY);
+                (aabb.min.xz() * transform.scale.xz(), aabb.max.xz() * transform.scale.xz())
+            },
+            Footprint::HalfExtents(x, z) => (Vec2::new(-x, -z), Vec2::new(x, z)),
+            Footprint::Clear => { return Vec::new(); },
+        };
+        let center = transform.translation.xz();
+        let rotation = Vec2::from_angle(transform.rotation.to_euler(EulerRot::YXZ).0);
+        let corners = [min, Vec2::new(min.x, max.y), max, Vec2::new(max.x, min.y)].map(|corner| center + Self::rotate(rotation, corner));
+        let (low, high) = corners.iter().fold((corners[0], corners[0]), |(low, high), corner| (low.min(*corner), high.max(*corner)));
+        let (low, high) = (space.position_to_index(low), space.position_to_index(high));
+
+        let mut cells = Vec::new();
+        for x in low.0..=high.0 {
+            for y in low.1..=high.1 {
+                let local = Self::rotate(Vec2::new(rotation.x, -rotation.y), space.index_to_position((x, y)) - center);
+                if local.cmpge(min).all() && local.cmple(max).all() {
+                    cells.push((x, y));
+                }
+            }
+        }
+        cells
+    }
+
+    ///Rotates a point on the ground the same way a yaw rotates a [`Transform`] about the y axis.
+    fn rotate(rotation: Vec2, point: Vec2) -> Vec2 {
+        Vec2::new(rotation.x * point.x + rotation.y * point.y, rotation.x * point.y - rotation.y * point.x)
+    }
+}
+
+///The cells each structure blocked when it was placed, so exactly those can be cleared when it goes away.
+#[derive(Debug, Default, Clone, Deref, DerefMut)]
+#[derive(Resource)]
+pub struct GridFootprints(pub HashMap<Entity, Vec<(isize, isize)>>);
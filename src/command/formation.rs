@@ -0,0 +1,116 @@
+use std::fmt::Display;
+use bevy::prelude::*;
+use serde::{Serialize, Deserialize};
+
+///Distance between neighbouring slots in a formation.
+pub const FORMATION_SPACING: f32 = 4.0;
+///How far a right click has to be dragged before it sets the formation's facing.
+pub const FORMATION_DRAG_THRESHOLD: f32 = 2.0;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+pub enum FormationShape {
+    ///Keeps the group's current layout, squeezed together if it is spread out.
+    #[default]
+    Relative,
+    Line,
+    Box,
+    Wedge,
+}
+
+impl FormationShape {
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Relative => Self::Line,
+            Self::Line => Self::Box,
+            Self::Box => Self::Wedge,
+            Self::Wedge => Self::Relative,
+        }
+    }
+
+    ///Where the `index`th of `count` slots sits, with x to the right and y towards the front.
+    fn offset(&self, index: usize, count: usize) -> Vec2 {
+        match self {
+            Self::Relative | Self::Line => {
+                Vec2::new(index as f32 - (count as f32 - 1.0) / 2.0, 0.0) * FORMATION_SPACING
+            },
+            Self::Box => {
+                let columns = (count as f32).sqrt().ceil().max(1.0) as usize;
+                let (row, column) = (index / columns, index % columns);
+                let row_length = columns.min(count - row * columns);
+                Vec2::new(column as f32 - (row_length as f32 - 1.0) / 2.0, -(row as f32)) * FORMATION_SPACING
+            },
+            Self::Wedge => {
+                let rank = (index + 1) / 2;
+                let side = if index % 2 == 1 { -1.0 } else { 1.0 };
+                Vec2::new(side * rank as f32, -(rank as f32)) * FORMATION_SPACING
+            },
+        }
+    }
+}
+
+impl Display for FormationShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormationShape::Relative => write!(f, "Relative"),
+            FormationShape::Line => write!(f, "Line"),
+            FormationShape::Box => write!(f, "Box"),
+            FormationShape::Wedge => write!(f, "Wedge"),
+        }
+    }
+}
+
+///The shape the local player's move orders use.
+#[derive(Debug, Default, Clone, Copy, Deref, DerefMut)]
+#[derive(Resource)]
+pub struct SelectedFormation(pub FormationShape);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Formation {
+    pub destination: Vec2,
+    ///Which way the front of the formation faces. Defaults to the direction the group is travelling.
+    pub facing: Option<Vec2>,
+    pub shape: FormationShape,
+}
+
+impl Formation {
+    ///One slot per position, in the same order.
+    pub fn slots(&self, positions: &[Vec2]) -> Vec<Vec2> {
+        if positions.is_empty() {
+            return Vec::new();
+        }
+        let centroid = positions.iter().sum::<Vec2>() / positions.len() as f32;
+        let heading = (self.destination - centroid).try_normalize().unwrap_or(Vec2::Y);
+        let facing = self.facing.and_then(|facing| facing.try_normalize()).unwrap_or(heading);
+        match self.shape {
+            FormationShape::Relative => {
+                let rotation = Vec2::from_angle(heading.angle_to(facing));
+                let limit = FORMATION_SPACING * (positions.len() as f32).sqrt();
+                let widest = positions.iter().map(|position| position.distance(centroid)).fold(0.0, f32::max);
+                let scale = if widest > limit { limit / widest } else { 1.0 };
+                positions.iter().map(|position| self.destination + rotation.rotate(*position - centroid) * scale).collect()
+            },
+            shape => {
+                let right = -facing.perp();
+                let slots = (0..positions.len())
+                    .map(|index| shape.offset(index, positions.len()))
+                    .map(|offset| self.destination + right * offset.x + facing * offset.y)
+                    .collect();
+                Self::assign(positions, slots)
+            },
+        }
+    }
+
+    ///Hands each slot, front to back, to the closest unit that doesn't have one yet.
+    fn assign(positions: &[Vec2], slots: Vec<Vec2>) -> Vec<Vec2> {
+        let mut assigned = positions.to_vec();
+        let mut free: Vec<usize> = (0..positions.len()).collect();
+        for slot in slots {
+            let Some((index, _)) = free.iter().enumerate().min_by(|(_, a), (_, b)| {
+                positions[**a].distance_squared(slot).total_cmp(&positions[**b].distance_squared(slot))
+            }) else { break; };
+            assigned[free.swap_remove(index)] = slot;
+        }
+        assigned
+    }
+}
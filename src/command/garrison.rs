@@ -0,0 +1,255 @@
+use std::f32::consts::TAU;
+use bevy::{platform::collections::HashMap, prelude::*};
+use serde::{Serialize, Deserialize};
+use crate::*;
+
+///How close a squad has to get to a structure before it can enter.
+pub const GARRISON_RANGE: f32 = 20.0;
+///How far from a structure's center occupants are placed when they leave.
+pub const EXIT_DISTANCE: f32 = 15.0;
+///How close a transport has to get to its unload point before letting its passengers out.
+pub const UNLOAD_RANGE: f32 = 5.0;
+
+#[derive(Debug, Default, Clone, Copy)]
+#[derive(Serialize, Deserialize)]
+pub struct AssetGarrison {
+    pub capacity: usize,
+    ///Fraction of max health each occupant loses when thrown out of a destroyed structure.
+    pub eject_damage: f32,
+}
+
+///Squads held inside a structure, or carried by a transport.
+#[derive(Debug, Default, Clone)]
+#[derive(Serialize, Deserialize)]
+#[derive(Component)]
+pub struct Garrison {
+    pub capacity: usize,
+    pub eject_damage: f32,
+    ///The squads inside. Entities are resolved from the snowflakes after loading.
+    pub occupants: Vec<(Snowflake, Option<Entity>)>,
+}
+
+impl Garrison {
+    pub fn is_full(&self) -> bool {
+        self.occupants.len() >= self.capacity
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.occupants.is_empty()
+    }
+}
+
+impl From<AssetGarrison> for Garrison {
+    fn from(asset_garrison: AssetGarrison) -> Self {
+        Self {
+            capacity: asset_garrison.capacity,
+            eject_damage: asset_garrison.eject_damage,
+            occupants: Vec::new(),
+        }
+    }
+}
+
+impl Slim for Garrison {
+    fn slim(&self) -> Option<Self> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(Self {
+                occupants: self.occupants.iter().map(|(snowflake, _)| (*snowflake, None)).collect(),
+                ..self.clone()
+            })
+        }
+    }
+}
+
+///A squad sitting inside a structure's [`Garrison`]. It is hidden, can't be targeted and doesn't move, but still fires.
+#[derive(Debug, Clone, Copy)]
+#[derive(Component)]
+pub struct Garrisoned(pub Entity);
+
+///A squad on its way to enter a structure.
+#[derive(Debug, Clone, Copy)]
+#[derive(Component)]
+pub struct GarrisonOrder(pub Entity);
+
+///A transport on its way to let its passengers out.
+#[derive(Debug, Clone, Copy)]
+#[derive(Component)]
+pub struct UnloadOrder(pub Vec2);
+
+pub struct GarrisonPlugin;
+
+impl GarrisonPlugin {
+    pub fn garrison_commands(
+        mut command_events: EventReader<CommandEvent>,
+        mut squads: Query<(Entity, &Transform, &TeamPlayer, &mut PathFinder), (With<Squad>, Without<Garrisoned>, Without<Dying>)>,
+        mut garrisons: Query<(&Transform, &TeamPlayer, &mut Garrison), Without<Dying>>,
+        mut transports: Query<(Entity, &Transform, &mut PathFinder), (With<Garrison>, Without<Squad>, Without<Dying>)>,
+        mut healths: Query<&mut Health, With<Garrisoned>>,
+        mut commands: Commands,
+    ) {
+        for command in command_events.read() {
+            match command.command {
+                CommandType::Garrison(target) => {
+                    let Ok((target_transform, target_teamplayer, _)) = garrisons.get(target) else { continue; };
+                    let target_pos = target_transform.translation.xz();
+                    squads.iter_mut().filter(|(entity, _, teamplayer, _)| command.objects.contains(entity) && *teamplayer == target_teamplayer).for_each(|(entity, transform, _, mut pathfinder)| {
+                        let pos = transform.translation.xz();
+                        let end = target_pos + (pos - target_pos).normalize_or_zero() * GARRISON_RANGE * 0.5;
+                        pathfinder.set_trip((pos, end));
+                        commands.entity(entity).insert(GarrisonOrder(target));
+                    });
+                },
+                CommandType::Ungarrison => {
+                    for object in command.objects.iter() {
+                        let Ok((transform, _, mut garrison)) = garrisons.get_mut(*object) else { continue; };
+                        Self::eject(&mut garrison, transform, 0.0, &mut healths, &mut commands);
+                    }
+                },
+                CommandType::Unload(point) => {
+                    transports.iter_mut().filter(|(entity, _, _)| command.objects.contains(entity)).for_each(|(entity, transform, mut pathfinder)| {
+                        pathfinder.set_trip((transform.translation.xz(), point));
+                        commands.entity(entity).insert(UnloadOrder(point));
+                    });
+                },
+                CommandType::Attack(_) | CommandType::Move(_) | CommandType::ReverseMove(_) | CommandType::Formation(_) | CommandType::AttackMove(_) | CommandType::Patrol(_)
+                | CommandType::Guard(_) | CommandType::Follow(_) => {
+                    for object in command.objects.iter() {
+                        if let Ok(mut entity_commands) = commands.get_entity(*object) {
+                            entity_commands.remove::<(GarrisonOrder, UnloadOrder)>();
+                        }
+                    }
+                },
+                _ => { },
+            }
+        }
+    }
+
+    pub fn enter_garrisons(
+        mut squads: Query<(Entity, &GarrisonOrder, &Snowflake, &mut Transform, &mut PathFinder, &mut Visibility), Without<Garrison>>,
+        mut garrisons: Query<(&Transform, &mut Garrison), Without<Dying>>,
+        mut commands: Commands,
+    ) {
+        squads.iter_mut().for_each(|(entity, order, snowflake, mut transform, mut pathfinder, mut visibility)| {
+            let Ok((target_transform, mut garrison)) = garrisons.get_mut(order.0) else {
+                commands.entity(entity).remove::<GarrisonOrder>();
+                return;
+            };
+            if transform.translation.xz().distance(target_transform.translation.xz()) > GARRISON_RANGE { return; }
+            commands.entity(entity).remove::<GarrisonOrder>();
+            if garrison.is_full() { return; }
+
+            garrison.occupants.push((*snowflake, Some(entity)));
+            transform.translation = target_transform.translation;
+            pathfinder.clear_trip();
+            pathfinder.clear_path();
+            *visibility = Visibility::Hidden;
+            commands.entity(entity).insert(Garrisoned(order.0)).remove::<Selectable>();
+        });
+    }
+
+    pub fn unload_transports(
+        mut transports: Query<(Entity, &UnloadOrder, &Transform, &mut Garrison), Without<Dying>>,
+        mut healths: Query<&mut Health, With<Garrisoned>>,
+        mut commands: Commands,
+    ) {
+        transports.iter_mut().for_each(|(entity, order, transform, mut garrison)| {
+            if transform.translation.xz().distance(order.0) > UNLOAD_RANGE { return; }
+            Self::eject(&mut garrison, transform, 0.0, &mut healths, &mut commands);
+            commands.entity(entity).remove::<UnloadOrder>();
+        });
+    }
+
+    ///Keeps occupants on top of whatever is holding them, so passengers move with their transport.
+    pub fn carry_occupants(
+        holders: Query<&Transform, (With<Garrison>, Changed<Transform>)>,
+        mut occupants: Query<(&Garrisoned, &mut Transform), Without<Garrison>>,
+    ) {
+        occupants.iter_mut().for_each(|(garrisoned, mut transform)| {
+            let Ok(holder) = holders.get(garrisoned.0) else { return; };
+            transform.translation = holder.translation;
+        });
+    }
+
+    ///Finds the squads inside loaded garrisons from their snowflakes. Occupants that no longer exist are dropped.
+    pub fn resolve_occupants(
+        mut garrisons: Query<(Entity, &mut Garrison), Added<Garrison>>,
+        mut squads: Query<(Entity, &Snowflake, &mut Visibility), (With<Squad>, Without<Garrisoned>)>,
+        mut commands: Commands,
+    ) {
+        let mut lookup: Option<HashMap<Snowflake, Entity>> = None;
+        garrisons.iter_mut().for_each(|(garrison_entity, mut garrison)| {
+            if garrison.occupants.iter().all(|(_, occupant)| occupant.is_some()) { return; }
+            let lookup = lookup.get_or_insert_with(|| squads.iter().map(|(entity, snowflake, _)| (*snowflake, entity)).collect());
+            for (snowflake, occupant) in garrison.occupants.iter_mut().filter(|(_, occupant)| occupant.is_none()) {
+                let Some(entity) = lookup.get(snowflake).copied() else { continue; };
+                let Ok((_, _, mut visibility)) = squads.get_mut(entity) else { continue; };
+                *occupant = Some(entity);
+                *visibility = Visibility::Hidden;
+                commands.entity(entity).insert(Garrisoned(garrison_entity)).remove::<Selectable>();
+            }
+            garrison.occupants.retain(|(_, occupant)| occupant.is_some());
+        });
+    }
+
+    ///Drops occupants that died or were despawned while inside, so they stop taking up room.
+    pub fn prune_occupants(
+        mut garrisons: Query<&mut Garrison>,
+        alive: Query<(), (With<Squad>, Without<Dying>)>,
+    ) {
+        garrisons.iter_mut().for_each(|mut garrison| {
+            let live = |(_, occupant): &(Snowflake, Option<Entity>)| occupant.is_some_and(|occupant| alive.contains(occupant));
+            if garrison.occupants.iter().all(live) { return; }
+            garrison.occupants.retain(live);
+        });
+    }
+
+    pub fn eject_on_death(
+        mut kills: EventReader<ObjectKilledEvent>,
+        mut garrisons: Query<(&Transform, &mut Garrison)>,
+        mut healths: Query<&mut Health, With<Garrisoned>>,
+        mut commands: Commands,
+    ) {
+        for ObjectKilledEvent(entity, _) in kills.read() {
+            let Ok((transform, mut garrison)) = garrisons.get_mut(*entity) else { continue; };
+            let eject_damage = garrison.eject_damage;
+            Self::eject(&mut garrison, transform, eject_damage, &mut healths, &mut commands);
+        }
+    }
+
+    ///Places every living occupant in a ring around the structure, taking `damage` as a fraction of their max health.
+    fn eject(garrison: &mut Garrison, transform: &Transform, damage: f32, healths: &mut Query<&mut Health, With<Garrisoned>>, commands: &mut Commands) {
+        let occupants: Vec<Entity> = garrison.occupants.drain(..)
+            .filter_map(|(_, occupant)| occupant)
+            .filter(|occupant| healths.get(*occupant).is_ok_and(|health| !health.is_dead()))
+            .collect();
+        let count = occupants.len();
+        for (index, occupant) in occupants.into_iter().enumerate() {
+            let angle = TAU * index as f32 / count as f32;
+            let position = transform.translation + Vec3::new(angle.cos(), 0.0, angle.sin()) * EXIT_DISTANCE;
+            if let Ok(mut health) = healths.get_mut(occupant) {
+                let max_health = health.max_health();
+                health.damage(max_health * damage, DamageTypes::explosive());
+            }
+            if let Ok(mut entity_commands) = commands.get_entity(occupant) {
+                entity_commands.remove::<Garrisoned>().insert((Transform::from_translation(position), Visibility::Inherited, Selectable::multiselect()));
+            }
+        }
+    }
+}
+
+impl Plugin for GarrisonPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, (
+                Self::garrison_commands,
+                Self::enter_garrisons.after(Self::garrison_commands),
+                Self::unload_transports.after(Self::garrison_commands),
+                Self::resolve_occupants,
+                Self::prune_occupants.after(Self::resolve_occupants).after(Self::enter_garrisons),
+            ).in_set(CommandSystems))
+            .add_systems(Update, Self::carry_occupants.after(CommandPlugin::follow_path))
+            .add_systems(Update, Self::eject_on_death.after(CombatSystems))
+        ;
+    }
+}
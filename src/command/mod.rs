@@ -1,21 +1,37 @@
 
 
+pub mod clearance;
 pub mod commander;
+pub mod flow_field;
+pub mod footprint;
+pub mod formation;
+pub mod garrison;
 pub mod navigation;
+pub mod orders;
 pub mod pathfinder;
-pub mod reference;
+pub mod repath;
 pub mod select;
 pub mod snowflake;
 pub mod squad;
+pub mod stance;
+pub mod steering;
 pub mod teamplayer;
 
+pub use clearance::*;
 pub use commander::*;
+pub use flow_field::*;
+pub use footprint::*;
+pub use formation::*;
+pub use garrison::*;
 pub use navigation::*;
+pub use orders::*;
 pub use pathfinder::*;
-pub use reference::*;
+pub use repath::*;
 pub use select::*;
 pub use snowflake::*;
 pub use squad::*;
+pub use stance::*;
+pub use steering::*;
 pub use teamplayer::*;
 
 // pub use pathing::*;
@@ -66,6 +82,14 @@ impl CommandEvent {
             None
         }
     }
+
+    pub fn stance(&self) -> Option<&Vec<Entity>> {
+        if self.command.is_stance() {
+            Some(&self.objects)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -73,7 +97,30 @@ pub enum CommandType {
     Activate,
     Attack(Entity),
     Build(BuildStatus),
+    ///Move as a group, keeping to the given formation.
+    Formation(Formation),
+    ///Stay with a friendly object without going after anything.
+    Follow(Entity),
+    ///Enter the given structure or transport's [`Garrison`].
+    Garrison(Entity),
+    ///Stay with a friendly object and fight whatever attacks it.
+    Guard(Entity),
     Move(Vec2),
+    ///Move while facing away from the destination, so vehicles keep their front armour towards what they are backing away from.
+    ReverseMove(Vec2),
+    ///Move to a point, stopping to fight anything the units pick up on the way.
+    AttackMove(Vec2),
+    ///Loop between the given point and where the units are now.
+    Patrol(Vec2),
+    ///Add an order to the end of the commanded units' [`Orders`] instead of replacing them.
+    Enqueue(Order),
+    ///Queue reinforcements for the commanded squads at the nearest friendly barracks.
+    Reinforce,
+    Stance(StanceType),
+    ///Empty the commanded structures' garrisons.
+    Ungarrison,
+    ///Drive the commanded transports to a point and let their passengers out there.
+    Unload(Vec2),
 }
 
 impl CommandType {
@@ -100,7 +147,14 @@ impl CommandType {
 
     pub fn is_move(&self) -> bool {
         match self {
-            Self::Move(_) => true,
+            Self::Move(_) | Self::ReverseMove(_) | Self::Formation(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_stance(&self) -> bool {
+        match self {
+            Self::Stance(_) => true,
             _ => false,
         }
     }
@@ -118,25 +172,51 @@ pub struct CommandPlugin;
 impl CommandPlugin {
     pub fn process_commands(
         mut commands: EventReader<CommandEvent>,
-        mut rand: ResMut<Random>,
-        mut pathfinders: Query<(Entity, &Transform, &mut PathFinder, &mut Navigator)>,
+        mut pathfinders: Query<(Entity, &Transform, &mut PathFinder, &mut Navigator, Option<&mut Stance>), Without<Dying>>,
     ) {
         for command in commands.read() {
+            let (formation, force_reverse) = match command.command {
+                CommandType::Move(destination) => (Some(Formation { destination, facing: None, shape: FormationShape::Relative }), false),
+                CommandType::ReverseMove(destination) => (Some(Formation { destination, facing: None, shape: FormationShape::Relative }), true),
+                CommandType::Formation(formation) => (Some(formation), false),
+                _ => (None, false),
+            };
+            if let Some(formation) = formation {
+                let mut members: Vec<_> = pathfinders.iter_mut().filter(|(entity, _, _, _, _)| command.objects.contains(entity)).collect();
+                let positions: Vec<Vec2> = members.iter().map(|(_, transform, _, _, _)| transform.translation.xz()).collect();
+                let slots = formation.slots(&positions);
+                let flow = (members.len() >= FLOW_FIELD_THRESHOLD && !force_reverse).then_some(formation.destination);
+                let slowest = (members.len() > 1).then(|| members.iter().map(|(_, _, _, navigator, _)| {
+                    if force_reverse { navigator.max_backwards_speed.abs() } else { navigator.max_forward_speed.abs() }
+                }).fold(f32::INFINITY, f32::min));
+                for ((_, transform, pathfinder, navigator, stance), (start, end)) in members.iter_mut().zip(positions.into_iter().zip(slots)) {
+                    let facing = (transform.rotation * -Vec3::Z).xz();
+                    match flow {
+                        Some(goal) => pathfinder.set_flow_trip((start, end), goal),
+                        None if force_reverse || navigator.prefers_reverse(facing, end - start) => pathfinder.set_reverse_trip((start, end)),
+                        None => pathfinder.set_trip((start, end)),
+                    }
+                    pathfinder.set_speed_limit(slowest);
+                    navigator.pursue = None;
+                    if let Some(stance) = stance {
+                        stance.post = Some(end);
+                        stance.chasing = false;
+                    }
+                }
+                continue;
+            }
             match command.command {
-                CommandType::Move(destination) => {
-                    let spread = (command.objects.len() as f32).sqrt();
-                    pathfinders.iter_mut().filter(|(entity, _, _, _)| command.objects.contains(entity)).for_each(|(_, transform, mut pathfinder, mut navigator)| {
-                        let start = transform.translation.xz();
-                        let end = destination + Vec2::new(rand.range(-spread, spread), rand.range(-spread, spread));
-                        pathfinder.set_trip((start, end));
-                        navigator.pursue = None;
-                    });
-                },
                 CommandType::Attack(target) => {
-                    pathfinders.iter_mut().filter(|(entity, _, _, _)| command.objects.contains(entity)).for_each(|(_, _, _, mut navigator)| {
+                    pathfinders.iter_mut().filter(|(entity, _, _, _, _)| command.objects.contains(entity)).for_each(|(_, _, _, mut navigator, _)| {
                         navigator.pursue = Some(target);
                     });
-                }
+                },
+                CommandType::Stance(stance_type) => {
+                    pathfinders.iter_mut().filter(|(entity, _, _, _, _)| command.objects.contains(entity)).for_each(|(_, _, _, _, stance)| {
+                        let Some(mut stance) = stance else { return; };
+                        stance.stance = stance_type;
+                    });
+                },
                 _ => { },
             }
         }
@@ -146,7 +226,7 @@ impl CommandPlugin {
         actors: Res<Commanders>,
         bounds: Res<MapBounds>,
         mut combat_world: ResMut<CombatWorld>,
-        query: Query<(Entity, &Transform, &Collider, &TeamPlayer)>
+        query: Query<(Entity, &Transform, &Collider, &TeamPlayer), (Without<Dying>, Without<Garrisoned>)>
     ) {
         if actors.is_changed() || bounds.is_changed() {
             *combat_world = CombatWorld::new(&actors, &bounds);
@@ -163,9 +243,9 @@ impl CommandPlugin {
     fn follow_path(
         mut gizmos: Gizmos,
         time: Res<Time>,
-        mut followers: Query<(&mut PathFinder, &mut Transform, &mut LinearVelocity, &Navigator)>,
+        mut followers: Query<(&mut PathFinder, &mut Transform, &mut LinearVelocity, &Navigator, Option<&StatusEffects>), (Without<Dying>, Without<Garrisoned>)>,
     ) {
-        followers.iter_mut().for_each(|(pathfinder, tran, _, _)| {
+        followers.iter_mut().for_each(|(pathfinder, tran, _, _, _)| {
             let Some(path) = pathfinder.path() else { return; };
             if let Some(x) = path.first() {
                 gizmos.line(tran.translation.xz().extend(1.0).xzy(), x.extend(1.0).xzy(), Color::srgba( 1.0, 0.2, 0.2, 1.0));
@@ -177,14 +257,23 @@ impl CommandPlugin {
             }
         });
 
-        followers.iter_mut().for_each(|(mut pathfinder, mut transform, mut velocity, navigator)| {
+        followers.iter_mut().for_each(|(mut pathfinder, mut transform, mut velocity, navigator, status_effects)| {
+            if status_effects.map_or(false, |effects| effects.is_shocked()) {
+                velocity.0.x = 0.0;
+                velocity.0.z = 0.0;
+                return;
+            }
+            let reverse = pathfinder.reverse();
+            let (heading, speed) = if reverse { (Vec3::Z, navigator.max_backwards_speed.abs()) } else { (-Vec3::Z, navigator.max_forward_speed.abs()) };
+            let speed = speed.min(pathfinder.speed_limit().unwrap_or(f32::INFINITY));
             let Some(path) = pathfinder.path_mut() else { return; };
             let y = transform.translation.y;
             match (path.get(0).cloned(), path.get(1).cloned()) {
                 (Some(first), Some(_second)) => {
                     let distance = first.distance(transform.translation.xz());
-                    let new_velocity = transform.rotation * -Vec3::Z  * navigator.max_forward_speed.abs().min(distance * 3.0);
-                    let desired_rotation = transform.looking_at(first.extend(y).xzy(), Vec3::Y).rotation;
+                    let new_velocity = transform.rotation * heading  * speed.min(distance * 3.0);
+                    let facing = if reverse { transform.translation.xz() * 2.0 - first } else { first };
+                    let desired_rotation = transform.looking_at(facing.extend(y).xzy(), Vec3::Y).rotation;
 
                     let max_turn_speed = navigator.max_turn_speed.unwrap_or(f32::INFINITY);
 
@@ -196,15 +285,16 @@ impl CommandPlugin {
                     transform.rotation = new_rotation;
 
                     if distance > 1.0 {
-                        velocity.0 = transform.rotation * -Vec3::Z * navigator.max_forward_speed.abs();
+                        velocity.0 = transform.rotation * heading * speed;
                     } else {
                         path.remove(0);
                     }
                 },
                 (Some(first), None) => {
                     let distance = first.distance(transform.translation.xz());
-                    let new_velocity = transform.rotation * -Vec3::Z  * navigator.max_forward_speed.abs().min(distance * 3.0);
-                    let desired_rotation = transform.looking_at(first.extend(y).xzy(), Vec3::Y).rotation;
+                    let new_velocity = transform.rotation * heading  * speed.min(distance * 3.0);
+                    let facing = if reverse { transform.translation.xz() * 2.0 - first } else { first };
+                    let desired_rotation = transform.looking_at(facing.extend(y).xzy(), Vec3::Y).rotation;
 
                     let max_turn_speed = navigator.max_turn_speed.unwrap_or(f32::INFINITY);
 
@@ -226,6 +316,7 @@ impl CommandPlugin {
                     velocity.0.x = 0.0;
                     velocity.0.z = 0.0;
                     pathfinder.clear_path();
+                    pathfinder.set_speed_limit(None);
                 }
             }
         });
@@ -242,11 +333,11 @@ impl Plugin for CommandPlugin {
         let bounds = app.world_mut().get_resource_or_insert_with(|| MapBounds::default()).clone();
         app.world_mut().get_resource_or_insert_with(|| CombatWorld::new(&commanders, &bounds));
 
-        app.world_mut().get_resource_or_insert_with(|| GridMap(DS2Map::new()));
+        app.world_mut().get_resource_or_insert_with(|| GridMap(DS2Map::new(), default()));
         app.world_mut().get_resource_or_insert_with(|| GridSpace::default());
 
         app
-            .add_plugins(PathFindingPlugin)
+            .add_plugins((PathFindingPlugin, GarrisonPlugin, SteeringPlugin, OrdersPlugin, RepathPlugin))
             .add_systems(Update, (
                 Self::process_commands.before(PathFindingSystems::PathFindingSystem),
                 Self::teamplayer_world_updater.after(Self::process_commands),
@@ -256,4 +347,4 @@ impl Plugin for CommandPlugin {
             )
         ;
     }
-}
\ No newline at end of file
+}
\ No newline at end of file
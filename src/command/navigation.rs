@@ -1,8 +1,12 @@
-use bevy::prelude::{Entity, Component};
+use std::f32::consts::PI;
+use bevy::prelude::{Entity, Component, Vec2};
 use serde::{Deserialize, Serialize};
 
 use crate::Slim;
 
+///Beyond this distance vehicles always turn around rather than back up.
+pub const MAX_REVERSE_DISTANCE: f32 = 40.0;
+
 #[derive(Debug, Clone, Copy)]
 #[derive(Serialize, Deserialize)]
 #[derive(Component)]
@@ -14,6 +18,21 @@ pub struct Navigator {
     pub pursue: Option<Entity>,
 }
 
+impl Navigator {
+    ///Whether backing up to a point is quicker than turning to face it and driving forward.
+    pub fn prefers_reverse(&self, facing: Vec2, offset: Vec2) -> bool {
+        let Some(turn_speed) = self.max_turn_speed.filter(|speed| *speed > 0.0) else { return false; };
+        let distance = offset.length();
+        if distance > MAX_REVERSE_DISTANCE || distance <= f32::EPSILON || self.max_backwards_speed.abs() <= f32::EPSILON {
+            return false;
+        }
+        let turn = facing.angle_to(offset).abs();
+        let forward = turn / turn_speed + distance / self.max_forward_speed.abs().max(f32::EPSILON);
+        let backward = (PI - turn) / turn_speed + distance / self.max_backwards_speed.abs();
+        backward < forward
+    }
+}
+
 impl Slim for Navigator {
     fn slim(&self) -> Option<Self> {
         self.pursue.is_some().then_some(*self)
@@ -0,0 +1,332 @@
+use std::collections::VecDeque;
+use bevy::{platform::collections::HashMap, prelude::*};
+use serde::{Serialize, Deserialize};
+use crate::*;
+
+///How close guarding and following units keep to their target.
+pub const GUARD_DISTANCE: f32 = 8.0;
+///How far from the guarded object enemies are picked up.
+pub const GUARD_RADIUS: f32 = 20.0;
+///Guarding units give up on an attacker once it gets this far from the guarded object.
+pub const GUARD_LEASH: f32 = 35.0;
+///Attack moving and patrolling units give up on a target once it gets this much further away than their weapons reach.
+pub const ENGAGE_LEASH: f32 = 15.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub enum Order {
+    Move(Vec2),
+    ///Chase down the object with this snowflake. The entity is resolved after loading.
+    Attack(Snowflake, #[serde(skip)] Option<Entity>),
+    ///Move to a point, stopping to fight anything met on the way.
+    AttackMove(Vec2),
+    ///Like [`Order::AttackMove`], but goes to the back of the queue once the point is reached.
+    Patrol(Vec2),
+    ///Stay near the object with this snowflake and fight whatever attacks it. Lasts until the object is gone.
+    Guard(Snowflake, #[serde(skip)] Option<Entity>),
+    ///Stay near the object with this snowflake without going after anything. Lasts until the object is gone.
+    Follow(Snowflake, #[serde(skip)] Option<Entity>),
+}
+
+impl Order {
+    fn color(&self) -> Color {
+        match self {
+            Order::Move(_) => Color::srgba(0.2, 1.0, 0.2, 1.0),
+            Order::Attack(_, _) => Color::srgba(1.0, 0.2, 0.2, 1.0),
+            Order::AttackMove(_) => Color::srgba(1.0, 0.6, 0.1, 1.0),
+            Order::Patrol(_) => Color::srgba(0.2, 0.4, 1.0, 1.0),
+            Order::Guard(_, _) => Color::srgba(0.2, 0.9, 0.9, 1.0),
+            Order::Follow(_, _) => Color::srgba(0.9, 0.9, 0.2, 1.0),
+        }
+    }
+
+    ///The object this order is aimed at, if any.
+    pub fn target(&self) -> Option<(Snowflake, Option<Entity>)> {
+        match *self {
+            Order::Attack(snowflake, entity) | Order::Guard(snowflake, entity) | Order::Follow(snowflake, entity) => Some((snowflake, entity)),
+            Order::Move(_) | Order::AttackMove(_) | Order::Patrol(_) => None,
+        }
+    }
+
+    fn target_mut(&mut self) -> Option<(Snowflake, &mut Option<Entity>)> {
+        match self {
+            Order::Attack(snowflake, entity) | Order::Guard(snowflake, entity) | Order::Follow(snowflake, entity) => Some((*snowflake, entity)),
+            Order::Move(_) | Order::AttackMove(_) | Order::Patrol(_) => None,
+        }
+    }
+}
+
+///Orders a unit works through one after another.
+#[derive(Debug, Default, Clone)]
+#[derive(Serialize, Deserialize)]
+#[derive(Component)]
+pub struct Orders {
+    pub current: Option<Order>,
+    pub queue: VecDeque<Order>,
+}
+
+impl Orders {
+    pub fn is_empty(&self) -> bool {
+        self.current.is_none() && self.queue.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.current = None;
+        self.queue.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Order> {
+        self.current.iter().chain(self.queue.iter())
+    }
+
+    ///Whether the current order lets the unit break off to fight.
+    pub fn engaging(&self) -> bool {
+        matches!(self.current, Some(Order::AttackMove(_)) | Some(Order::Patrol(_)))
+    }
+}
+
+impl Slim for Orders {
+    fn slim(&self) -> Option<Self> {
+        (!self.is_empty()).then_some(self.clone())
+    }
+}
+
+pub struct OrdersPlugin;
+
+impl OrdersPlugin {
+    pub fn order_commands(
+        mut commands: EventReader<CommandEvent>,
+        snowflakes: Query<&Snowflake>,
+        mut units: Query<(Entity, &Transform, &mut Orders, &mut PathFinder, &mut Navigator, Option<&mut Stance>), Without<Dying>>,
+    ) {
+        for command in commands.read() {
+            if let CommandType::AttackMove(destination) = command.command {
+                let mut members: Vec<_> = units.iter_mut().filter(|(entity, _, _, _, _, _)| command.objects.contains(entity)).collect();
+                let positions: Vec<Vec2> = members.iter().map(|(_, transform, _, _, _, _)| transform.translation.xz()).collect();
+                let slots = Formation { destination, facing: None, shape: FormationShape::Relative }.slots(&positions);
+                for ((_, _, orders, pathfinder, navigator, stance), (position, slot)) in members.iter_mut().zip(positions.into_iter().zip(slots)) {
+                    orders.clear();
+                    orders.current = Some(Order::AttackMove(slot));
+                    pathfinder.set_trip((position, slot));
+                    navigator.pursue = None;
+                    if let Some(stance) = stance {
+                        stance.post = Some(slot);
+                        stance.chasing = false;
+                    }
+                }
+                continue;
+            }
+            units.iter_mut().filter(|(entity, _, _, _, _, _)| command.objects.contains(entity)).for_each(|(entity, transform, mut orders, mut pathfinder, mut navigator, stance)| {
+                match command.command {
+                    CommandType::Guard(target) | CommandType::Follow(target) => {
+                        orders.clear();
+                        if target == entity { return; }
+                        let snowflake = snowflakes.get(target).copied().unwrap_or_default();
+                        orders.current = Some(match command.command {
+                            CommandType::Guard(_) => Order::Guard(snowflake, Some(target)),
+                            _ => Order::Follow(snowflake, Some(target)),
+                        });
+                        pathfinder.clear_trip();
+                        pathfinder.clear_path();
+                        navigator.pursue = None;
+                    },
+                    CommandType::Attack(_) | CommandType::Move(_) | CommandType::ReverseMove(_) | CommandType::Formation(_) => {
+                        orders.clear();
+                    },
+                    CommandType::Patrol(point) => {
+                        let position = transform.translation.xz();
+                        orders.clear();
+                        orders.current = Some(Order::Patrol(point));
+                        orders.queue.push_back(Order::Patrol(position));
+                        pathfinder.set_trip((position, point));
+                        navigator.pursue = None;
+                        if let Some(mut stance) = stance {
+                            stance.post = Some(point);
+                            stance.chasing = false;
+                        }
+                    },
+                    CommandType::Enqueue(order) => {
+                        orders.queue.push_back(order);
+                    },
+                    _ => { },
+                }
+            });
+        }
+    }
+
+    ///Starts the next order once a unit has nothing left to do. Attack moving and patrolling units break off to fight the
+    ///first enemy they pick up and carry on afterwards.
+    pub fn process_orders(
+        combat_world: Res<CombatWorld>,
+        mut damage_events: EventReader<DamageEvent>,
+        snowflakes: Query<&Snowflake>,
+        team_players: Query<&TeamPlayer>,
+        transforms: Query<&Transform, (Without<Dying>, Without<Garrisoned>)>,
+        mut units: Query<(&Transform, &TeamPlayer, &mut Orders, &mut PathFinder, &mut Navigator, &WeaponSet, Option<&mut Stance>), Without<Dying>>,
+    ) {
+        let attackers: HashMap<Entity, Entity> = damage_events.read().filter_map(|event| event.attacker.map(|attacker| (event.victim, attacker))).collect();
+        units.iter_mut().for_each(|(transform, teamplayer, mut orders, mut pathfinder, mut navigator, weapon_set, mut stance)| {
+            let position = transform.translation.xz();
+            if let (Some(Order::Attack(_, Some(target))), Some(Order::Guard(_, Some(guarded)))) = (orders.current, orders.queue.front().copied()) {
+                let strayed = match (transforms.get(target), transforms.get(guarded)) {
+                    (Ok(target), Ok(guarded)) => target.translation.xz().distance(guarded.translation.xz()) > GUARD_LEASH,
+                    _ => false,
+                };
+                if strayed {
+                    orders.current = None;
+                    pathfinder.clear_trip();
+                    pathfinder.clear_path();
+                    navigator.pursue = None;
+                }
+            }
+
+            if let (Some(Order::Attack(_, Some(target))), Some(Order::AttackMove(_) | Order::Patrol(_))) = (orders.current, orders.queue.front().copied()) {
+                let reach = weapon_set.max_range().unwrap_or(0.0) + ENGAGE_LEASH;
+                if transforms.get(target).map_or(false, |target| target.translation.xz().distance(position) > reach) {
+                    orders.current = None;
+                    pathfinder.clear_trip();
+                    pathfinder.clear_path();
+                    navigator.pursue = None;
+                }
+            }
+
+            if let Some(current @ (Order::Guard(_, target) | Order::Follow(_, target))) = orders.current {
+                match target.and_then(|target| transforms.get(target).ok().map(|transform| (target, transform.translation.xz()))) {
+                    Some((target, target_pos)) => {
+                        if let Order::Guard(_, _) = current {
+                            let attacker = attackers.get(&target).copied()
+                                .filter(|attacker| combat_world.is_enemy(*attacker, *teamplayer, &team_players).unwrap_or(false))
+                                .filter(|attacker| transforms.get(*attacker).map_or(false, |attacker| attacker.translation.xz().distance(target_pos) <= GUARD_LEASH))
+                                .or_else(|| combat_world.search_enemies(*teamplayer, target_pos, GUARD_RADIUS).into_iter()
+                                    .min_by(|(_, a), (_, b)| a.distance(target_pos).total_cmp(&b.distance(target_pos)))
+                                    .map(|(enemy, _)| enemy));
+                            if let Some(attacker) = attacker {
+                                orders.queue.push_front(current);
+                                orders.current = Some(Order::Attack(snowflakes.get(attacker).copied().unwrap_or_default(), Some(attacker)));
+                                pathfinder.clear_trip();
+                                pathfinder.clear_path();
+                                navigator.pursue = Some(attacker);
+                                return;
+                            }
+                        }
+                        if position.distance(target_pos) > GUARD_DISTANCE {
+                            let end = target_pos + (position - target_pos).normalize_or_zero() * GUARD_DISTANCE * 0.5;
+                            let stale = pathfinder.path().as_ref().and_then(|path| path.last()).map_or(true, |last| last.distance(end) > GUARD_DISTANCE * 0.5);
+                            if stale && pathfinder.trip().is_none() {
+                                pathfinder.set_trip((position, end));
+                            }
+                            if let Some(stance) = stance.as_mut() {
+                                stance.post = Some(end);
+                                stance.chasing = false;
+                            }
+                        }
+                        return;
+                    },
+                    //The guarded object is gone, so move on to whatever is next.
+                    None => { orders.current = None; },
+                }
+            }
+
+            if let Some(current) = orders.current.filter(|_| orders.engaging()) {
+                let target = weapon_set.weapons.iter().filter(|weapon| !weapon.heals).find_map(|weapon| match weapon.target {
+                    Target::AutoTarget(target) => Some(target),
+                    _ => None,
+                });
+                if let Some(target) = target {
+                    orders.queue.push_front(current);
+                    orders.current = Some(Order::Attack(snowflakes.get(target).copied().unwrap_or_default(), Some(target)));
+                    pathfinder.clear_trip();
+                    pathfinder.clear_path();
+                    navigator.pursue = Some(target);
+                    return;
+                }
+            }
+
+            let busy = pathfinder.trip().is_some() || pathfinder.path().is_some() || navigator.pursue.is_some();
+            if busy || orders.is_empty() { return; }
+
+            if let Some(Order::Patrol(point)) = orders.current.take() {
+                orders.queue.push_back(Order::Patrol(point));
+            }
+            let Some(order) = orders.queue.pop_front() else { return; };
+            match order {
+                Order::Move(point) | Order::AttackMove(point) | Order::Patrol(point) => {
+                    pathfinder.set_trip((position, point));
+                    if let Some(mut stance) = stance {
+                        stance.post = Some(point);
+                        stance.chasing = false;
+                    }
+                },
+                Order::Attack(_, Some(target)) => {
+                    navigator.pursue = Some(target);
+                },
+                Order::Attack(_, None) => { return; },
+                Order::Guard(_, _) | Order::Follow(_, _) => { },
+            }
+            orders.current = Some(order);
+        });
+    }
+
+    ///Finds the targets of freshly loaded orders. Orders whose target no longer exists are dropped, since they would
+    ///otherwise hold the unit forever.
+    pub fn resolve_orders(
+        mut units: Query<&mut Orders, Added<Orders>>,
+        snowflakes: Query<(Entity, &Snowflake)>,
+    ) {
+        let mut lookup: Option<HashMap<Snowflake, Entity>> = None;
+        units.iter_mut().for_each(|mut orders| {
+            if !orders.iter().any(|order| matches!(order.target(), Some((_, None)))) { return; }
+            let lookup = lookup.get_or_insert_with(|| snowflakes.iter().map(|(entity, snowflake)| (*snowflake, entity)).collect());
+            let Orders { current, queue } = &mut *orders;
+            for order in current.iter_mut().chain(queue.iter_mut()) {
+                let Some((snowflake, entity @ None)) = order.target_mut() else { continue; };
+                *entity = lookup.get(&snowflake).copied();
+            }
+            if matches!(orders.current.and_then(|order| order.target()), Some((_, None))) {
+                orders.current = None;
+            }
+            orders.queue.retain(|order| !matches!(order.target(), Some((_, None))));
+        });
+    }
+
+    pub fn draw_orders(
+        mut gizmos: Gizmos,
+        units: Query<(&Transform, &Orders), (Without<Dying>, Without<Garrisoned>)>,
+        transforms: Query<&Transform>,
+    ) {
+        units.iter().for_each(|(transform, orders)| {
+            let mut previous = transform.translation.xz();
+            let mut first_patrol = None;
+            for order in orders.iter() {
+                let point = match order {
+                    Order::Move(point) | Order::AttackMove(point) | Order::Patrol(point) => *point,
+                    Order::Attack(_, target) | Order::Guard(_, target) | Order::Follow(_, target) => {
+                        let Some(point) = target.and_then(|target| transforms.get(target).ok()).map(|target| target.translation.xz()) else { continue; };
+                        point
+                    },
+                };
+                if let Order::Patrol(point) = order {
+                    first_patrol = first_patrol.or(Some(*point));
+                }
+                gizmos.line(previous.extend(1.5).xzy(), point.extend(1.5).xzy(), order.color());
+                previous = point;
+            }
+            if let Some(first) = first_patrol {
+                gizmos.line(previous.extend(1.5).xzy(), first.extend(1.5).xzy(), Order::Patrol(first).color());
+            }
+        });
+    }
+}
+
+impl Plugin for OrdersPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, (
+                Self::order_commands.after(CommandPlugin::process_commands),
+                Self::resolve_orders,
+                Self::process_orders.after(Self::order_commands).after(Self::resolve_orders),
+            ).in_set(CommandSystems))
+            .add_systems(Update, Self::draw_orders)
+        ;
+    }
+}
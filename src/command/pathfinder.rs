@@ -1,18 +1,261 @@
-use bevy::prelude::*;
+use std::sync::{Arc, Mutex, RwLock};
+use bevy::{platform::collections::{HashMap, HashSet}, prelude::*};
+use avian3d::prelude::Collider;
 use serde::{Serialize, Deserialize};
-use crossbeam_channel::{unbounded, bounded, Sender, Receiver};
+use crossbeam_channel::{unbounded, Sender, Receiver};
 use pathing::*;
 
 use crate::*;
 
-#[derive(Resource, Deref)]
-pub struct PFStreamInput(Sender<(Entity, Vec2, Vec2)>);
+#[derive(Debug, Clone)]
+pub enum PathRequest {
+    Single(Entity, Vec2, Vec2),
+    ///Trips that all end around the same goal. They are traced through one shared [`FlowField`].
+    Flow(Vec2, Vec<(Entity, Vec2, Vec2)>),
+}
+
+///Where the pathfinding workers send finished paths. `ticket` and `generation` let the main thread throw out results that
+///were overtaken by a newer request or computed against an old [`GridMap`].
+#[derive(Debug, Clone)]
+pub struct PathResult {
+    pub entity: Entity,
+    pub path: Vec<Vec2>,
+    pub ticket: u64,
+    pub generation: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+#[derive(Resource)]
+pub struct PathFindingSettings {
+    ///How many threads compute paths.
+    pub workers: usize,
+}
+
+impl Default for PathFindingSettings {
+    fn default() -> Self {
+        Self {
+            workers: std::thread::available_parallelism().map_or(1, |cores| cores.get().saturating_sub(1).max(1)),
+        }
+    }
+}
+
+///A [`GridMap`] with its blocked cells gathered once, so searches on it don't have to.
+struct GridLayer {
+    map: Arc<GridMap>,
+    blocked: HashSet<(isize, isize)>,
+}
+
+impl GridLayer {
+    fn new(map: GridMap) -> Self {
+        let blocked = map.blocked();
+        Self { map: Arc::new(map), blocked }
+    }
+
+    fn path(&self, space: &GridSpace, start: Vec2, end: Vec2) -> Vec<Vec2> {
+        self.map.path(space, start, end, &self.blocked)
+    }
+}
+
+struct PathGrid {
+    generation: u64,
+    base: Arc<GridLayer>,
+    space: GridSpace,
+    ///Copies of `base` grown for wider units, built the first time something of that clearance asks for a path.
+    layers: Mutex<HashMap<u8, Arc<GridLayer>>>,
+}
+
+impl PathGrid {
+    fn new(generation: u64, map: GridMap, space: GridSpace) -> Self {
+        Self { generation, base: Arc::new(GridLayer::new(map)), space, layers: Mutex::new(HashMap::new()) }
+    }
+
+    fn layer(&self, clearance: u8) -> Arc<GridLayer> {
+        if clearance == 0 {
+            return self.base.clone();
+        }
+        if let Some(layer) = self.layers.lock().ok().and_then(|layers| layers.get(&clearance).cloned()) {
+            return layer;
+        }
+        let layer = Arc::new(GridLayer::new(self.base.map.clearance_layer(clearance)));
+        match self.layers.lock() {
+            Ok(mut layers) => layers.entry(clearance).or_insert(layer).clone(),
+            Err(_) => layer,
+        }
+    }
 
-#[derive(Resource, Deref)]
-pub struct PFStreamOutput(Receiver<(Entity, Vec<Vec2>)>);
+    ///Paths through the unit's layer. When the start or end only has room for smaller units, they are moved to the nearest
+    ///cell the unit fits in. No path is found if there is none.
+    fn path(&self, layer: &GridLayer, start: Vec2, end: Vec2) -> Vec<Vec2> {
+        let path = layer.path(&self.space, start, end);
+        if !path.is_empty() || std::ptr::eq(layer, self.base.as_ref()) {
+            return path;
+        }
+        match (layer.map.nearest_free(&self.space, start, &layer.blocked), layer.map.nearest_free(&self.space, end, &layer.blocked)) {
+            (Some(start), Some(end)) => layer.path(&self.space, start, end),
+            _ => Vec::new(),
+        }
+    }
+}
 
-#[derive(Resource, Deref)]
-pub struct PFStreamReset(Sender<(GridMap, GridSpace)>);
+///A pool of blocking pathfinding workers. Only the latest request for each entity is computed.
+#[derive(Resource)]
+pub struct PathFindingService {
+    jobs: Sender<(u64, u8, PathRequest)>,
+    results: Receiver<PathResult>,
+    tickets: Arc<Mutex<HashMap<Entity, u64>>>,
+    grid: Arc<RwLock<Arc<PathGrid>>>,
+    requested: HashMap<Entity, ((Vec2, Vec2), Option<Vec2>, u8)>,
+    next_ticket: u64,
+    generation: u64,
+}
+
+impl PathFindingService {
+    pub fn new(workers: usize, map: GridMap, space: GridSpace) -> Self {
+        let (jobs, queue) = unbounded::<(u64, u8, PathRequest)>();
+        let (sender, results) = unbounded::<PathResult>();
+        let tickets: Arc<Mutex<HashMap<Entity, u64>>> = Arc::new(Mutex::new(HashMap::new()));
+        let grid = Arc::new(RwLock::new(Arc::new(PathGrid::new(0, map, space))));
+        for _ in 0..workers.max(1) {
+            let queue = queue.clone();
+            let sender = sender.clone();
+            let tickets = tickets.clone();
+            let grid = grid.clone();
+            std::thread::spawn(move || Self::work(queue, sender, tickets, grid));
+        }
+        Self {
+            jobs,
+            results,
+            tickets,
+            grid,
+            requested: HashMap::new(),
+            next_ticket: 0,
+            generation: 0,
+        }
+    }
+
+    ///Runs until the service is dropped, sleeping while there is nothing to do.
+    fn work(
+        queue: Receiver<(u64, u8, PathRequest)>,
+        sender: Sender<PathResult>,
+        tickets: Arc<Mutex<HashMap<Entity, u64>>>,
+        grid: Arc<RwLock<Arc<PathGrid>>>,
+    ) {
+        let mut fields: HashMap<((isize, isize), u8), FlowField> = HashMap::new();
+        let mut fields_generation = 0;
+        for (ticket, clearance, request) in queue.iter() {
+            let Ok(grid) = grid.read().map(|grid| grid.clone()) else { return; };
+            if grid.generation != fields_generation {
+                fields.clear();
+                fields_generation = grid.generation;
+            }
+            let layer = grid.layer(clearance);
+            let current = |entity: &Entity| tickets.lock().map_or(false, |tickets| tickets.get(entity) == Some(&ticket));
+            let send = |entity: Entity, path: Vec<Vec2>| sender.send(PathResult { entity, path, ticket, generation: grid.generation }).is_ok();
+            match request {
+                PathRequest::Single(entity, start, end) => {
+                    if !current(&entity) { continue; }
+                    if !send(entity, grid.path(&layer, start, end)) { return; }
+                },
+                PathRequest::Flow(goal, trips) => {
+                    let trips: Vec<(Entity, Vec2, Vec2)> = trips.into_iter().filter(|(entity, _, _)| current(entity)).collect();
+                    if trips.is_empty() { continue; }
+                    let goal_index = grid.space.position_to_index(goal);
+                    let starts: Vec<(isize, isize)> = trips.iter().map(|(_, start, _)| grid.space.position_to_index(*start)).collect();
+                    let key = (goal_index, clearance);
+                    let cached = fields.get(&key).map_or(false, |field| starts.iter().all(|start| field.contains(*start)));
+                    if !cached {
+                        if fields.len() >= FLOW_FIELD_CACHE {
+                            fields.clear();
+                        }
+                        fields.insert(key, layer.map.flow_field(goal_index, FlowField::bounds(goal_index, &starts), &layer.blocked));
+                    }
+                    let field = &fields[&key];
+                    for ((entity, start, end), start_index) in trips.into_iter().zip(starts) {
+                        //Follow the field only until the unit is as close to the goal as its own slot, then path to the slot.
+                        let path = field.cost(grid.space.position_to_index(end))
+                            .and_then(|slot_cost| field.trace(start_index, slot_cost))
+                            .map(|nodes| {
+                                let split = nodes.last().map_or(start, |split| grid.space.index_to_position(*split));
+                                nodes.into_iter().skip(1).map(|n| grid.space.index_to_position(n))
+                                    .chain(grid.path(&layer, split, end))
+                                    .chain(std::iter::once(end))
+                                    .collect()
+                            })
+                            .unwrap_or_else(|| grid.path(&layer, start, end));
+                        if !send(entity, path) { return; }
+                    }
+                },
+            }
+        }
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    ///Swaps in a new grid. Anything computed against the old one will be rejected.
+    pub fn update_grid(&mut self, map: GridMap, space: GridSpace) {
+        self.generation += 1;
+        if let Ok(mut grid) = self.grid.write() {
+            *grid = Arc::new(PathGrid::new(self.generation, map, space));
+        }
+        self.requested.clear();
+    }
+
+    ///The current grid as seen by units of the given clearance, building that layer if nothing has needed it yet.
+    pub fn layer(&self, clearance: u8) -> Option<Arc<GridMap>> {
+        self.grid.read().ok().map(|grid| grid.layer(clearance).map.clone())
+    }
+
+    ///Queues a request unless the same trip is already being worked on. Older requests for the same entities are dropped.
+    ///Paths are planned on the grid layer for `clearance`, so they leave wide units enough room.
+    pub fn submit(&mut self, clearance: u8, request: PathRequest) {
+        let (trips, flow) = match &request {
+            PathRequest::Single(entity, start, end) => (vec![(*entity, *start, *end)], None),
+            PathRequest::Flow(goal, trips) => (trips.clone(), Some(*goal)),
+        };
+        let fresh: Vec<(Entity, Vec2, Vec2)> = trips.into_iter().filter(|(entity, start, end)| self.requested.get(entity) != Some(&((*start, *end), flow, clearance))).collect();
+        if fresh.is_empty() { return; }
+
+        self.next_ticket += 1;
+        let ticket = self.next_ticket;
+        if let Ok(mut tickets) = self.tickets.lock() {
+            for (entity, start, end) in fresh.iter() {
+                tickets.insert(*entity, ticket);
+                self.requested.insert(*entity, ((*start, *end), flow, clearance));
+            }
+        }
+        let request = match flow {
+            Some(goal) => PathRequest::Flow(goal, fresh),
+            None => PathRequest::Single(fresh[0].0, fresh[0].1, fresh[0].2),
+        };
+        let _ = self.jobs.send((ticket, clearance, request));
+    }
+
+    pub fn results(&self) -> Vec<PathResult> {
+        self.results.try_iter().collect()
+    }
+
+    ///Whether a result is the latest for its entity and was computed against the current grid. Accepted results are
+    ///forgotten, so the same trip can be requested again.
+    pub fn accept(&mut self, result: &PathResult) -> bool {
+        let Ok(mut tickets) = self.tickets.lock() else { return false; };
+        if tickets.get(&result.entity) != Some(&result.ticket) || result.generation != self.generation {
+            return false;
+        }
+        tickets.remove(&result.entity);
+        self.requested.remove(&result.entity);
+        true
+    }
+
+    ///Drops whatever is pending for an entity, such as one that no longer exists.
+    pub fn forget(&mut self, entity: Entity) {
+        if let Ok(mut tickets) = self.tickets.lock() {
+            tickets.remove(&entity);
+        }
+        self.requested.remove(&entity);
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet)]
 pub enum PathFindingSystems {
@@ -21,11 +264,38 @@ pub enum PathFindingSystems {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Resource)]
-pub struct GridMap(pub DS2Map);
+pub struct GridMap(pub DS2Map, #[serde(skip)] pub TerrainCosts);
 
 impl GridMap {
-    fn path_find(&self, start: GridPos, end: GridPos) -> Option<Vec<GridPos>> {
-        self.0.find_path(start, end)
+    ///Every blocked cell, gathered into a set for quick lookups.
+    pub fn blocked(&self) -> HashSet<(isize, isize)> {
+        self.0.blocks().into_iter().copied().collect()
+    }
+
+    ///Plain grid search when all ground costs the same, otherwise a search that steers around rough terrain.
+    fn path_find(&self, start: GridPos, end: GridPos, blocked: &HashSet<(isize, isize)>) -> Option<Vec<GridPos>> {
+        if self.1.0.is_empty() {
+            return self.0.find_path(start, end);
+        }
+        self.weighted_path(start, end, FlowField::bounds(end, &[start]), blocked)
+            .or_else(|| self.0.find_path(start, end))
+    }
+
+    fn path(&self, space: &GridSpace, start: Vec2, end: Vec2, blocked: &HashSet<(isize, isize)>) -> Vec<Vec2> {
+        let start_index = space.position_to_index(start);
+        let end_index = space.position_to_index(end);
+        if start_index == end_index {
+            return Vec::new();
+        }
+        self.path_find(start_index, end_index, blocked)
+            .map(|mut nodes| {
+                nodes.remove(0);
+                nodes
+                    .iter()
+                    .map(|n| space.index_to_position((n.0, n.1)))
+                    .collect()
+            })
+            .unwrap_or(Vec::default())
     }
 }
 
@@ -68,11 +338,73 @@ pub struct PathFinder {
 
     trip: Option<(Vec2, Vec2)>,
     path: Option<Vec<Vec2>>,
+    ///The shared goal of a group move, if this trip should be traced through a [`FlowField`].
+    #[serde(default)]
+    flow: Option<Vec2>,
+    ///Holds a unit back to the pace of the slowest member of its formation.
+    #[serde(default)]
+    speed_limit: Option<f32>,
+    ///Drive the path backwards, facing away from it.
+    #[serde(default)]
+    reverse: bool,
+    ///Which [`GridMap`] layer this unit paths on. Measured from its collider, see [`clearance`].
+    #[serde(skip)]
+    clearance: u8,
 }
 
 impl PathFinder {
     pub fn set_trip(&mut self, (start, end): (Vec2, Vec2)) {
         self.trip = Some((start, end));
+        self.flow = None;
+        self.speed_limit = None;
+        self.reverse = false;
+    }
+
+    pub fn set_flow_trip(&mut self, (start, end): (Vec2, Vec2), goal: Vec2) {
+        self.trip = Some((start, end));
+        self.flow = Some(goal);
+        self.speed_limit = None;
+        self.reverse = false;
+    }
+
+    pub fn set_reverse_trip(&mut self, (start, end): (Vec2, Vec2)) {
+        self.trip = Some((start, end));
+        self.flow = None;
+        self.speed_limit = None;
+        self.reverse = true;
+    }
+
+    pub fn flow(&self) -> Option<Vec2> {
+        self.flow
+    }
+
+    pub fn set_speed_limit(&mut self, speed_limit: Option<f32>) {
+        self.speed_limit = speed_limit;
+    }
+
+    pub fn speed_limit(&self) -> Option<f32> {
+        self.speed_limit
+    }
+
+    pub fn reverse(&self) -> bool {
+        self.reverse
+    }
+
+    ///Whether a reverse trip is still being planned or driven.
+    pub fn reversing(&self) -> bool {
+        self.reverse && (self.trip.is_some() || self.path.is_some())
+    }
+
+    pub fn clearance(&self) -> u8 {
+        self.clearance
+    }
+
+    ///Plans the rest of the current trip again from `position`, keeping how it is driven. The old path is followed until the
+    ///new one arrives.
+    pub fn repath(&mut self, position: Vec2) -> bool {
+        let Some(destination) = self.path.as_ref().and_then(|path| path.last().copied()).or(self.trip.map(|(_, end)| end)) else { return false; };
+        self.trip = Some((position, destination));
+        true
     }
 
     pub fn clear_trip(&mut self) {
@@ -110,86 +442,88 @@ pub struct PathFindingPlugin;
 
 impl PathFindingPlugin {
     pub fn setup(
+        settings: Res<PathFindingSettings>,
         map: Res<GridMap>,
         space: Res<GridSpace>,
         mut commands: Commands,
     ) {
-        let (input, reader) = unbounded::<(Entity, Vec2, Vec2)>();
-        let (sender, output) = unbounded::<(Entity, Vec<Vec2>)>();
-        let (reset, resets) = bounded::<(GridMap, GridSpace)>(1);
-        let mut map = (*map).clone();
-        let mut space = space.clone();
-        std::thread::spawn(move || {
-            loop {
-                for (grid_map, grid_space) in resets.try_iter() {
-                    map = grid_map;
-                    space = grid_space;
-                }
-                for (entity, start, end) in reader.try_iter() {
-                    let start_index = space.position_to_index(start);
-                    let end_index = space.position_to_index(end);
-                    if start_index == end_index {
-                        let _ = sender.try_send((entity, Vec::new()));
-                    }
-                    let path = map
-                        .path_find(start_index, end_index)
-                        .map(|mut nodes| {
-                            nodes.remove(0);
-                            nodes
-                                .iter()
-                                .map(|n| space.index_to_position((n.0, n.1)))
-                                .collect()
-                        })
-                        .unwrap_or(Vec::default());
-                    let _ = sender.try_send((entity, path));
-                }
-            }
-        });
-        commands.insert_resource(PFStreamInput(input));
-        commands.insert_resource(PFStreamOutput(output));
-        commands.insert_resource(PFStreamReset(reset));
+        commands.insert_resource(PathFindingService::new(settings.workers, map.clone(), *space));
     }
 
     pub fn grid_update(
         grid: Res<GridMap>,
         space: Res<GridSpace>,
-        reset: Res<PFStreamReset>,
+        mut service: ResMut<PathFindingService>,
     ) {
         if grid.is_changed() || space.is_changed() {
-            let _ = reset.try_send((grid.clone(), space.clone()));
+            service.update_grid(grid.clone(), *space);
         }
     }
 
+    pub fn measure_clearance(
+        space: Res<GridSpace>,
+        mut path_finders: Query<(&mut PathFinder, Ref<Collider>, &Transform)>,
+    ) {
+        path_finders.iter_mut().for_each(|(mut pathfinder, collider, transform)| {
+            if !(pathfinder.is_added() || collider.is_changed() || space.is_changed()) { return; }
+            let clearance = clearance(&collider, transform, &space);
+            if pathfinder.clearance != clearance {
+                pathfinder.clearance = clearance;
+            }
+        });
+    }
+
     pub fn path_finding_system(
-        input: Res<PFStreamInput>,
-        output: Res<PFStreamOutput>,
+        mut service: ResMut<PathFindingService>,
         mut path_finders: ParamSet<(
             Query<(Entity, &PathFinder), Changed<PathFinder>>,
             Query<&mut PathFinder>,
         )>,
     ) {
+        let mut flows: Vec<(Vec2, u8, Vec<(Entity, Vec2, Vec2)>)> = Vec::new();
         path_finders.p0().iter().for_each(|(entity, pathfinder)| {
-            if let Some((start, end)) = pathfinder.trip() {
-                let _ = input.try_send((entity, *start, *end));
+            let Some((start, end)) = pathfinder.trip() else { return; };
+            let clearance = pathfinder.clearance();
+            match pathfinder.flow() {
+                Some(goal) => match flows.iter_mut().find(|(flow_goal, flow_clearance, _)| *flow_goal == goal && *flow_clearance == clearance) {
+                    Some((_, _, trips)) => trips.push((entity, *start, *end)),
+                    None => flows.push((goal, clearance, vec![(entity, *start, *end)])),
+                },
+                None => service.submit(clearance, PathRequest::Single(entity, *start, *end)),
             }
         });
+        for (goal, clearance, trips) in flows {
+            service.submit(clearance, PathRequest::Flow(goal, trips));
+        }
 
-        output.try_iter().for_each(|(entity, path)| {
+        let generation = service.generation();
+        for result in service.results() {
             let mut p1 = path_finders.p1();
-            let Ok(mut path_finder) = p1.get_mut(entity) else { return; };
-            path_finder.set_path(path);
-            path_finder.clear_trip();
-        })
+            let Ok(mut path_finder) = p1.get_mut(result.entity) else {
+                service.forget(result.entity);
+                continue;
+            };
+            if service.accept(&result) {
+                path_finder.set_path(result.path);
+                path_finder.clear_trip();
+            } else if result.generation != generation && path_finder.trip().is_some() {
+                //Computed against an outdated grid, so flag the trip to be asked for again.
+                path_finder.set_changed();
+            }
+        }
     }
 }
 
 impl Plugin for PathFindingPlugin {
     fn build(&self, app: &mut App) {
         app
+            .init_resource::<PathFindingSettings>()
             .add_systems(Startup, Self::setup)
             .add_systems(Update, (
                 Self::grid_update
                     .in_set(PathFindingSystems::GridSpaceUpdateSystem),
+                Self::measure_clearance
+                    .before(PathFindingSystems::PathFindingSystem),
                 Self::path_finding_system
                     .in_set(PathFindingSystems::PathFindingSystem)
                     .after(PathFindingSystems::GridSpaceUpdateSystem)
@@ -204,4 +538,108 @@ fn resources_exist(
     grid_space: Option<Res<GridSpace>>,
 ) -> bool {
     grid_map.is_some() && grid_space.is_some()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+    ///A floor well under what a debug build manages, so the throughput test only trips on real slowdowns.
+    const MIN_PATHS_PER_SECOND: f32 = 100.0;
+
+    ///A service over a grid with a wall down the middle, so paths have to go around it.
+    fn service(workers: usize) -> PathFindingService {
+        let mut map = DS2Map::new();
+        map.add_objects((-20..=20).map(|y| (0, y)).collect());
+        map.precompute();
+        PathFindingService::new(workers, GridMap(map, default()), GridSpace::new())
+    }
+
+    fn wait(service: &PathFindingService, count: usize) -> Vec<PathResult> {
+        let started = Instant::now();
+        let mut results = Vec::new();
+        while results.len() < count && started.elapsed() < TIMEOUT {
+            results.extend(service.results());
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(results.len(), count);
+        results
+    }
+
+    #[test]
+    fn stale_tickets_are_dropped() {
+        let mut service = service(1);
+        let entity = Entity::from_raw(1);
+        service.submit(0, PathRequest::Single(entity, Vec2::new(-10.0, 0.0), Vec2::new(10.0, 0.0)));
+        let old = wait(&service, 1);
+        service.submit(0, PathRequest::Single(entity, Vec2::new(-10.0, 5.0), Vec2::new(10.0, 5.0)));
+        assert!(!service.accept(&old[0]));
+        let new = wait(&service, 1);
+        assert!(service.accept(&new[0]));
+        assert_eq!(new[0].path.last(), Some(&Vec2::new(10.0, 5.0)));
+    }
+
+    #[test]
+    fn old_generations_are_rejected() {
+        let mut service = service(1);
+        let entity = Entity::from_raw(1);
+        let (start, end) = (Vec2::new(-10.0, 0.0), Vec2::new(10.0, 0.0));
+        service.submit(0, PathRequest::Single(entity, start, end));
+        let old = wait(&service, 1);
+        let map = service.layer(0).unwrap();
+        service.update_grid((*map).clone(), GridSpace::new());
+        assert!(!service.accept(&old[0]));
+        service.submit(0, PathRequest::Single(entity, start, end));
+        let new = wait(&service, 1);
+        assert_eq!(new[0].generation, service.generation());
+        assert!(service.accept(&new[0]));
+    }
+
+    #[test]
+    fn only_the_latest_request_is_computed() {
+        let mut service = service(1);
+        let entity = Entity::from_raw(1);
+        //Hold the worker back until every request is queued.
+        let grid = service.grid.clone();
+        let lock = grid.write().unwrap();
+        for y in 0..100 {
+            service.submit(0, PathRequest::Single(entity, Vec2::new(-10.0, y as f32), Vec2::new(10.0, y as f32)));
+        }
+        drop(lock);
+        let mut results = wait(&service, 1);
+        std::thread::sleep(Duration::from_millis(50));
+        results.extend(service.results());
+        assert_eq!(results.len(), 1);
+        assert!(service.accept(&results[0]));
+        assert_eq!(results[0].path.last(), Some(&Vec2::new(10.0, 99.0)));
+    }
+
+    ///Replaces every request once before it can be answered. Each entity gets exactly one path, at most one stale result
+    ///per entity reaches the main thread, and the service keeps above [`MIN_PATHS_PER_SECOND`]. The `bench` subcommand
+    ///reports the actual numbers.
+    #[test]
+    fn throughput() {
+        let workers = PathFindingSettings::default().workers;
+        let mut service = service(workers);
+        let paths = 500;
+        let started = Instant::now();
+        for index in 0..paths {
+            let y = index as f32 * 0.1 - 25.0;
+            service.submit(0, PathRequest::Single(Entity::from_raw(index), Vec2::new(-30.0, -y), Vec2::new(30.0, y)));
+            service.submit(0, PathRequest::Single(Entity::from_raw(index), Vec2::new(-30.0, y), Vec2::new(30.0, -y)));
+        }
+        let (mut accepted, mut rejected) = (0, 0);
+        while accepted < paths && started.elapsed() < TIMEOUT {
+            for result in service.results() {
+                if service.accept(&result) { accepted += 1; } else { rejected += 1; }
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        let elapsed = started.elapsed().as_secs_f32();
+        assert_eq!(accepted, paths);
+        assert!(rejected <= paths, "{} stale results for {} entities", rejected, paths);
+        assert!(paths as f32 / elapsed >= MIN_PATHS_PER_SECOND, "{:.1} paths/s", paths as f32 / elapsed);
+    }
+}
@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+use crate::*;
+
+///How long a unit may go without closing in on its next waypoint before it counts as stuck.
+pub const STUCK_TIME: f32 = 2.0;
+///How much closer to its next waypoint a unit has to get to count as making progress.
+pub const STUCK_PROGRESS: f32 = 1.0;
+///How long to wait after a repath before trying another. Doubles with each attempt that doesn't get the unit moving.
+pub const REPATH_BACKOFF: f32 = 0.5;
+///Repaths a unit gets before it gives up on its trip.
+pub const MAX_REPATHS: u8 = 4;
+
+///Tracks whether a unit is getting anywhere along its path. Added to everything with a [`PathFinder`].
+#[derive(Debug, Default, Clone, Copy)]
+#[derive(Component)]
+pub struct PathProgress {
+    destination: Option<Vec2>,
+    waypoints: usize,
+    best: f32,
+    stalled: f32,
+    cooldown: f32,
+    attempts: u8,
+    ///The grid changed underneath the path.
+    invalidated: bool,
+    ///Set when the unit ran out of repaths and dropped its trip. Cleared by the next trip.
+    gave_up: bool,
+}
+
+impl PathProgress {
+    pub fn gave_up(&self) -> bool {
+        self.gave_up
+    }
+
+    pub fn attempts(&self) -> u8 {
+        self.attempts
+    }
+}
+
+pub struct RepathPlugin;
+
+impl RepathPlugin {
+    pub fn track_progress(
+        path_finders: Query<Entity, Added<PathFinder>>,
+        mut commands: Commands,
+    ) {
+        path_finders.iter().for_each(|entity| {
+            commands.entity(entity).insert(PathProgress::default());
+        });
+    }
+
+    ///Flags every path that now runs through a blocked cell.
+    pub fn invalidate_paths(
+        grid_map: Res<GridMap>,
+        space: Res<GridSpace>,
+        mut path_finders: Query<(&Transform, &PathFinder, &mut PathProgress), (Without<Dying>, Without<Garrisoned>)>,
+    ) {
+        if !grid_map.is_changed() { return; }
+        let blocked = grid_map.blocked();
+        if blocked.is_empty() { return; }
+        let step = space.scale.min_element() * 0.5;
+        path_finders.iter_mut().for_each(|(transform, pathfinder, mut progress)| {
+            let Some(path) = pathfinder.path() else { return; };
+            let start = space.position_to_index(transform.translation.xz());
+            let mut previous = transform.translation.xz();
+            let crosses = path.iter().any(|waypoint| {
+                let segment = *waypoint - previous;
+                let samples = (segment.length() / step).ceil().max(1.0) as usize;
+                let from = previous;
+                previous = *waypoint;
+                (1..=samples).any(|sample| {
+                    let cell = space.position_to_index(from + segment * (sample as f32 / samples as f32));
+                    cell != start && blocked.contains(&cell)
+                })
+            });
+            if crosses {
+                progress.invalidated = true;
+            }
+        });
+    }
+
+    ///Plans a new trip from where a unit is when its path was invalidated or it stopped making progress. Repaths back off
+    ///and the unit gives up on the trip after [`MAX_REPATHS`].
+    pub fn repath_stuck(
+        time: Res<Time>,
+        mut path_finders: Query<(&Transform, &mut PathFinder, &mut PathProgress, Option<&StatusEffects>), (Without<Dying>, Without<Garrisoned>)>,
+    ) {
+        path_finders.iter_mut().for_each(|(transform, mut pathfinder, mut progress, status_effects)| {
+            progress.cooldown = (progress.cooldown - time.delta_secs()).max(0.0);
+            let position = transform.translation.xz();
+            let Some((next, destination, waypoints)) = pathfinder.path().as_ref()
+                .and_then(|path| Some((*path.first()?, *path.last()?, path.len()))) else {
+                progress.destination = None;
+                progress.invalidated = false;
+                progress.stalled = 0.0;
+                return;
+            };
+
+            if progress.destination.map_or(true, |previous| previous.distance(destination) > STUCK_PROGRESS) {
+                *progress = PathProgress { destination: Some(destination), waypoints, best: position.distance(next), ..default() };
+                return;
+            }
+            //Waiting on a new path or held in place by something else.
+            if pathfinder.trip().is_some() || status_effects.map_or(false, |effects| effects.is_shocked()) {
+                progress.stalled = 0.0;
+                return;
+            }
+
+            let distance = position.distance(next);
+            if waypoints < progress.waypoints {
+                progress.attempts = 0;
+                progress.stalled = 0.0;
+                progress.best = distance;
+            } else if waypoints > progress.waypoints || distance < progress.best - STUCK_PROGRESS {
+                progress.stalled = 0.0;
+                progress.best = progress.best.min(distance);
+            } else {
+                progress.stalled += time.delta_secs();
+            }
+            progress.waypoints = waypoints;
+
+            if !(progress.invalidated || progress.stalled > STUCK_TIME) || progress.cooldown > 0.0 { return; }
+            if progress.attempts >= MAX_REPATHS {
+                pathfinder.clear_trip();
+                pathfinder.set_path(Vec::new());
+                progress.gave_up = true;
+                progress.invalidated = false;
+                return;
+            }
+            pathfinder.repath(position);
+            progress.attempts += 1;
+            progress.cooldown = REPATH_BACKOFF * 2.0f32.powi(progress.attempts as i32 - 1);
+            progress.invalidated = false;
+            progress.stalled = 0.0;
+            progress.best = f32::INFINITY;
+        });
+    }
+}
+
+impl Plugin for RepathPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, (
+                Self::track_progress,
+                Self::invalidate_paths,
+                Self::repath_stuck.after(Self::invalidate_paths),
+            ).before(PathFindingSystems::PathFindingSystem))
+        ;
+    }
+}
@@ -9,7 +9,34 @@ use crate::{ObjectType, Slim};
 #[derive(Component)]
 pub struct Squad {
     pub buffer: f32,
+    ///The surviving members of the squad.
     pub members: Vec<(ObjectType, Option<Entity>)>,
+    ///The members of the squad at full strength.
+    #[serde(default)]
+    pub roster: Vec<ObjectType>,
+}
+
+impl Squad {
+    pub fn full_strength(&self) -> usize {
+        self.roster.len().max(self.members.len())
+    }
+
+    pub fn missing(&self) -> usize {
+        self.full_strength() - self.members.len()
+    }
+
+    ///Fraction of the squad still alive.
+    pub fn strength(&self) -> f32 {
+        match self.full_strength() {
+            0 => 1.0,
+            full => self.members.len() as f32 / full as f32,
+        }
+    }
+
+    ///How many members should still be alive at the given health percent.
+    pub fn survivors(&self, health_percent: f32) -> usize {
+        ((health_percent.clamp(0.0, 1.0) * self.full_strength() as f32).ceil() as usize).min(self.full_strength())
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -24,12 +51,17 @@ impl From<AssetSquad> for Squad {
         Self {
             buffer: prefab_squad.buffer,
             members: prefab_squad.members.iter().map(|object_type| (object_type.clone(), None)).collect(),
+            roster: prefab_squad.members,
         }
     }
 }
 
 impl Slim for Squad {
     fn slim(&self) -> Option<Self> {
-        None
+        if self.missing() > 0 {
+            Some(self.clone())
+        } else {
+            None
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,94 @@
+use std::fmt::Display;
+use bevy::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::Slim;
+
+pub const DEFAULT_LEASH: f32 = 60.0;
+///Fraction of the leash a defensive unit will chase before giving up.
+pub const DEFENSIVE_LEASH_RATIO: f32 = 0.25;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+pub enum StanceType {
+    ///Chases anything it auto targets, as long as it stays within the leash of its post.
+    #[default]
+    Aggressive,
+    ///Chases a short distance, then returns to its post.
+    Defensive,
+    ///Never moves on its own, only engages what is already in range.
+    HoldPosition,
+    ///Never auto targets.
+    HoldFire,
+}
+
+impl Display for StanceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StanceType::Aggressive => write!(f, "Aggressive"),
+            StanceType::Defensive => write!(f, "Defensive"),
+            StanceType::HoldPosition => write!(f, "Hold Position"),
+            StanceType::HoldFire => write!(f, "Hold Fire"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize)]
+#[derive(Component)]
+pub struct Stance {
+    pub stance: StanceType,
+    pub leash: f32,
+    ///Where this unit returns to after chasing. Set whenever the unit is given a move order.
+    pub post: Option<Vec2>,
+    ///Whether the unit is currently off its post chasing an auto target.
+    #[serde(skip)]
+    pub chasing: bool,
+}
+
+impl Stance {
+    pub fn new(stance: StanceType) -> Self {
+        Self {
+            stance,
+            ..default()
+        }
+    }
+
+    pub fn auto_targets(&self) -> bool {
+        self.stance != StanceType::HoldFire
+    }
+
+    ///How far from its post this unit may travel while chasing, [None] if it should never chase.
+    pub fn chase_distance(&self) -> Option<f32> {
+        match self.stance {
+            StanceType::Aggressive => Some(self.leash),
+            StanceType::Defensive => Some(self.leash * DEFENSIVE_LEASH_RATIO),
+            StanceType::HoldPosition => None,
+            StanceType::HoldFire => None,
+        }
+    }
+
+    pub fn returns_to_post(&self) -> bool {
+        match self.stance {
+            StanceType::Aggressive | StanceType::Defensive => true,
+            StanceType::HoldPosition | StanceType::HoldFire => false,
+        }
+    }
+}
+
+impl Default for Stance {
+    fn default() -> Self {
+        Self {
+            stance: StanceType::default(),
+            leash: DEFAULT_LEASH,
+            post: None,
+            chasing: false,
+        }
+    }
+}
+
+impl Slim for Stance {
+    fn slim(&self) -> Option<Self> {
+        (self.stance != StanceType::default() || self.post.is_some()).then_some(*self)
+    }
+}
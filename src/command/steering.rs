@@ -0,0 +1,97 @@
+use bevy::{platform::collections::HashMap, prelude::*};
+use avian3d::prelude::LinearVelocity;
+use crate::*;
+
+///How far ahead and around a unit it looks for others to steer around.
+pub const STEERING_RADIUS: f32 = 6.0;
+///Units closer than this push apart. Kept under [`FORMATION_SPACING`] so formations don't spread themselves out.
+pub const SEPARATION_RADIUS: f32 = 3.0;
+pub const SEPARATION_STRENGTH: f32 = 8.0;
+///How fast an idle unit steps aside for a moving ally.
+pub const SHUFFLE_SPEED: f32 = 4.0;
+///Cosine of the half-angle in front of a moving unit where others make it slow down and queue.
+const QUEUE_CONE: f32 = 0.7;
+///The slowest a queueing unit will crawl, so separation can still slide it around whatever is in the way.
+const MIN_BRAKE: f32 = 0.2;
+
+#[derive(Debug, Clone, Copy)]
+struct Mover {
+    position: Vec2,
+    velocity: Vec2,
+    moving: bool,
+    teamplayer: TeamPlayer,
+}
+
+pub struct SteeringPlugin;
+
+impl SteeringPlugin {
+    ///Adjusts the velocities [`CommandPlugin::follow_path`] set. Moving units keep apart, queue behind slower units in front of them
+    ///and sidestep to their right when meeting head on. Idle units shuffle out of the way of moving allies.
+    pub fn steer(
+        combat_world: Res<CombatWorld>,
+        mut movers: Query<(Entity, &Transform, &mut LinearVelocity, &PathFinder, &TeamPlayer, Option<&StatusEffects>), (With<Navigator>, Without<Dying>, Without<Garrisoned>)>,
+    ) {
+        let snapshot: HashMap<Entity, Mover> = movers.iter().map(|(entity, transform, velocity, pathfinder, teamplayer, _)| (entity, Mover {
+            position: transform.translation.xz(),
+            velocity: velocity.0.xz(),
+            moving: pathfinder.path().is_some(),
+            teamplayer: *teamplayer,
+        })).collect();
+
+        movers.iter_mut().for_each(|(entity, _, mut velocity, _, _, status_effects)| {
+            //Shocked units stay put, but others still steer around them.
+            if status_effects.is_some_and(|effects| effects.is_shocked()) { return; }
+            let Some(mover) = snapshot.get(&entity).copied() else { return; };
+            let heading = mover.velocity.try_normalize();
+            let mut separation = Vec2::ZERO;
+            let mut shuffle = Vec2::ZERO;
+            let mut brake: f32 = 1.0;
+
+            for (other, _) in combat_world.search_nearby(mover.position, STEERING_RADIUS) {
+                if other == entity { continue; }
+                let Some(neighbour) = snapshot.get(&other) else { continue; };
+                let offset = mover.position - neighbour.position;
+                let distance = offset.length();
+                if distance >= STEERING_RADIUS { continue; }
+                let away = offset.try_normalize().unwrap_or_else(|| Vec2::from_angle(entity.index() as f32));
+                let ally = neighbour.teamplayer.team() == mover.teamplayer.team();
+
+                if distance < SEPARATION_RADIUS && (mover.moving || ally) {
+                    separation += away * (1.0 - distance / SEPARATION_RADIUS);
+                }
+
+                if let Some(heading) = heading.filter(|_| mover.moving) {
+                    if heading.dot(-away) > QUEUE_CONE {
+                        if neighbour.velocity.dot(heading) < mover.velocity.length() {
+                            brake = brake.min((distance / STEERING_RADIUS).max(MIN_BRAKE));
+                        }
+                        if neighbour.moving && neighbour.velocity.dot(heading) < 0.0 {
+                            separation += -heading.perp() * (1.0 - distance / STEERING_RADIUS);
+                        }
+                    }
+                } else if ally && neighbour.moving && neighbour.velocity.dot(offset) > 0.0 {
+                    let their_heading = neighbour.velocity.normalize_or_zero();
+                    let lateral = offset - their_heading * offset.dot(their_heading);
+                    shuffle += lateral.try_normalize().unwrap_or(their_heading.perp()) * (1.0 - distance / STEERING_RADIUS);
+                }
+            }
+
+            let steered = if mover.moving {
+                let speed = mover.velocity.length();
+                (mover.velocity * brake + separation * SEPARATION_STRENGTH).clamp_length_max(speed)
+            } else {
+                (shuffle * SHUFFLE_SPEED + separation * SEPARATION_STRENGTH * 0.5).clamp_length_max(SHUFFLE_SPEED)
+            };
+            velocity.0.x = steered.x;
+            velocity.0.z = steered.y;
+        });
+    }
+}
+
+impl Plugin for SteeringPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, Self::steer.after(CommandPlugin::follow_path).before(GarrisonPlugin::carry_occupants))
+        ;
+    }
+}
@@ -17,6 +17,8 @@ pub struct SaveObjects {
     pub crane_yards: Vec<CraneYardDisk>,
     pub barracks: Vec<BarracksDisk>,
     pub factories: Vec<FactoryDisk>,
+    #[serde(default)]
+    pub defense_turrets: Vec<DefenseTurretDisk>,
     pub marine_squads: Vec<MarineSquadDisk>,
     pub resource_nodes: Vec<ResourceNodeDisk>,
     pub armadillos: Vec<ArmadilloDisk>,
@@ -32,6 +34,7 @@ pub struct LoadingStatus {
     pub resource_nodes_loaded: Option<bool>,
     pub barracks_loaded: Option<bool>,
     pub factories_loaded: Option<bool>,
+    pub defense_turrets_loaded: Option<bool>,
     pub marines_loaded: Option<bool>,
     pub armadillos_loaded: Option<bool>,
     pub tanks_loaded: Option<bool>,
@@ -44,19 +47,21 @@ impl LoadingStatus {
         & self.crane_yards_loaded.unwrap_or(true)
         & self.resource_nodes_loaded.unwrap_or(true)
         & self.factories_loaded.unwrap_or(true)
+        & self.defense_turrets_loaded.unwrap_or(true)
         & self.marines_loaded.unwrap_or(true)
         & self.armadillos_loaded.unwrap_or(true)
         & self.tanks_loaded.unwrap_or(true)
     }
 }
 
-pub type CraneYardDiskQuery<'a> = (&'a Snowflake, &'a Health, &'a Queues, &'a TeamPlayer, &'a Transform);
-pub type BarracksDiskQuery<'a> = (&'a Snowflake, &'a Health, &'a Queues, &'a TeamPlayer, &'a Transform);
-pub type FactoryDiskQuery<'a> = (&'a Snowflake, &'a Health, &'a Queues, &'a TeamPlayer, &'a Transform);
-pub type MarineSquadDiskQuery<'a> = (&'a Snowflake, &'a Health, &'a Squad, &'a PathFinder, &'a Navigator, &'a WeaponSet, &'a LinearVelocity, &'a TeamPlayer, &'a Transform);
+pub type CraneYardDiskQuery<'a> = (&'a Snowflake, &'a Health, &'a StatusEffects, &'a Queues, &'a Garrison, &'a TeamPlayer, &'a Transform);
+pub type BarracksDiskQuery<'a> = (&'a Snowflake, &'a Health, &'a StatusEffects, &'a Queues, &'a Garrison, &'a TeamPlayer, &'a Transform);
+pub type FactoryDiskQuery<'a> = (&'a Snowflake, &'a Health, &'a StatusEffects, &'a Queues, &'a Garrison, &'a TeamPlayer, &'a Transform);
+pub type DefenseTurretDiskQuery<'a> = (&'a Snowflake, &'a Health, &'a StatusEffects, &'a WeaponSet, &'a Turrets, &'a TeamPlayer, &'a Transform);
+pub type MarineSquadDiskQuery<'a> = (&'a Snowflake, &'a Health, &'a StatusEffects, &'a Squad, &'a PathFinder, &'a Navigator, &'a WeaponSet, &'a Stance, &'a Orders, &'a Veterancy, &'a LinearVelocity, &'a TeamPlayer, &'a Transform);
 pub type ResourceNodeDiskQuery<'a> = (&'a Snowflake, &'a ResourceNodePlatforms, &'a TeamPlayer, &'a Transform);
-pub type ArmadilloDiskQuery<'a> = (&'a Snowflake, &'a Health, &'a PathFinder, &'a Navigator, &'a WeaponSet, &'a LinearVelocity, &'a TeamPlayer, &'a Transform);
-pub type TankBaseDiskQuery<'a> = (&'a Snowflake, &'a Health, &'a PathFinder, &'a Navigator, &'a WeaponSet, &'a Reference, &'a LinearVelocity, &'a TeamPlayer, &'a Transform);
+pub type ArmadilloDiskQuery<'a> = (&'a Snowflake, &'a Health, &'a StatusEffects, &'a PathFinder, &'a Navigator, &'a WeaponSet, &'a Stance, &'a Orders, &'a Garrison, &'a Veterancy, &'a LinearVelocity, &'a TeamPlayer, &'a Transform);
+pub type TankBaseDiskQuery<'a> = (&'a Snowflake, &'a Health, &'a StatusEffects, &'a PathFinder, &'a Navigator, &'a WeaponSet, &'a Stance, &'a Orders, &'a Veterancy, &'a Turrets, &'a LinearVelocity, &'a TeamPlayer, &'a Transform);
 
 #[derive(Debug, Clone, Copy)]
 pub struct DiskPlugin;
@@ -79,6 +84,7 @@ impl DiskPlugin {
             Query<ResourceNodeDiskQuery, With<ResourceNodePlatforms>>,
             Query<ArmadilloDiskQuery, With<Armadillo>>,
             Query<TankBaseDiskQuery, With<TankBase>>,
+            Query<DefenseTurretDiskQuery, With<DefenseTurret>>,
         ),
     ) {
         let mut file = None;
@@ -91,11 +97,13 @@ impl DiskPlugin {
             let resource_nodes = object.4.iter().map(|object| ResourceNodeDisk::from(object)).collect();
             let armadillos = object.5.iter().map(|object| ArmadilloDisk::from(object)).collect();
             let tanks = object.6.iter().map(|object| TankBaseDisk::from(object)).collect();
+            let defense_turrets = object.7.iter().map(|object| DefenseTurretDisk::from(object)).collect();
 
             let objects = SaveObjects {
                 crane_yards,
                 barracks,
                 factories,
+                defense_turrets,
                 marine_squads,
                 resource_nodes,
                 armadillos,
@@ -104,7 +112,7 @@ impl DiskPlugin {
 
             let base_save_state = BaseSaveState {
                 commanders: actors.clone(),
-                map: *map,
+                map: map.clone(),
                 objects
             };
 
@@ -151,6 +159,7 @@ impl DiskPlugin {
         for object in &objects.resource_nodes { status.resource_nodes_loaded = Some(false); load_objects.write(object.clone().into()); }
         for object in &objects.barracks { status.barracks_loaded = Some(false); load_objects.write(object.clone().into()); }
         for object in &objects.factories { status.factories_loaded = Some(false); load_objects.write(object.clone().into()); }
+        for object in &objects.defense_turrets { status.defense_turrets_loaded = Some(false); load_objects.write(object.clone().into()); }
         for object in &objects.marine_squads { status.marines_loaded = Some(false); load_objects.write(object.clone().into()); }
         for object in &objects.armadillos { status.armadillos_loaded = Some(false); load_objects.write(object.clone().into()); }
         for object in &objects.tanks { status.tanks_loaded = Some(false); load_objects.write(object.clone().into()); }
@@ -178,4 +187,4 @@ impl Plugin for DiskPlugin {
             .add_systems(Update, Self::finish_loading_game.run_if(resource_exists::<SaveFile>))
         ;
     }
-}
\ No newline at end of file
+}
\ No newline at end of file
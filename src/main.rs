@@ -1,5 +1,7 @@
+use std::time::{Duration, Instant};
 use clap::*;
 use bevy::{image::ImageSamplerDescriptor, prelude::*, window::PresentMode};
+use pathing::DS2Map;
 use t5f::*;
 
 // #![windows_subsystem = "windows"]
@@ -19,31 +21,70 @@ pub struct Args {
 #[derive(Subcommand, Debug, Clone)]
 enum Mode {
     #[clap(short_flag('c'))]
-    Client,
+    Client {
+        ///Writes every damage event to this file as JSON lines.
+        #[clap(long)]
+        damage_log: Option<String>,
+        ///How many threads compute paths. Defaults to one less than the number of cores.
+        #[clap(long)]
+        path_workers: Option<usize>,
+    },
     #[clap(short_flag('s'))]
     Server,
     #[clap(short_flag('a'))]
     Asset {
         path: String,
-    }
+    },
+    ///Bakes a map's terrain grid and writes it back into the map file.
+    #[clap(short_flag('m'))]
+    Bake {
+        path: String,
+    },
+    ///Runs the pathfinding workers without a window and reports their throughput.
+    #[clap(short_flag('b'))]
+    Bench {
+        #[clap(long, default_value_t = 1000)]
+        paths: usize,
+        #[clap(long)]
+        workers: Option<usize>,
+        ///Send every trip to one goal as a single flow field request.
+        #[clap(long)]
+        flow: bool,
+        ///Plan on the grid layer for units this many cells wide.
+        #[clap(long, default_value_t = 0)]
+        clearance: u8,
+    },
 }
 
 pub fn main() {
     match Args::try_parse() {
         Ok(arg) => {
             match arg.mode {
-                Mode::Client => client(),
+                Mode::Client {damage_log, path_workers, } => client(damage_log, path_workers),
                 Mode::Server => server(),
                 Mode::Asset {path, } => asset(path),
+                Mode::Bake {path, } => bake(path),
+                Mode::Bench {paths, workers, flow, clearance, } => bench(paths, workers, flow, clearance),
             };
         },
-        Err(_) => client(),
+        Err(_) => client(None, None),
     };
     // if Args::parse().server { server(); } else { client(); }
 }
 
-pub fn client() {
-    App::new()
+pub fn client(damage_log: Option<String>, path_workers: Option<usize>) {
+    let mut app = App::new();
+    if let Some(workers) = path_workers {
+        app.insert_resource(PathFindingSettings { workers });
+    }
+    if let Some(path) = damage_log {
+        match DamageRecorder::create(&path) {
+            Ok(recorder) => { app.insert_resource(recorder); },
+            Err(e) => { log::error!("Could not open damage log {}: {}", path, e); },
+        }
+    }
+
+    app
         .insert_resource(ClearColor(CLEAR_COLOR))
         .insert_resource(UiHit::<CLICK_BUFFER>{ hitting: [false; CLICK_BUFFER], holding: false, })
         .insert_resource(Random::<WichmannHill>::seeded(123.456))
@@ -98,4 +139,64 @@ pub fn asset(path: String) {
     let trimesh = extract_trimesh(format!("{}/assets/{}", std::env::current_dir().unwrap().to_str().unwrap(), path)).unwrap();
     let code = encode(trimesh).unwrap();
     println!("{}", code);
-}
\ No newline at end of file
+}
+
+pub fn bake(path: String) {
+    let path = format!("{}/assets/{}", std::env::current_dir().unwrap().to_str().unwrap(), path);
+    let bytes = std::fs::read(&path).unwrap();
+    let mut map: MapAsset = ron::de::from_bytes(&bytes).unwrap();
+    map.terrain = None;
+    map.bake_terrain();
+    if let Some(terrain) = map.terrain.as_ref() {
+        println!("{} blocked cells, {} rough cells", terrain.blocked.len(), terrain.costs.len());
+    }
+    let config = ron::ser::PrettyConfig::new().extensions(ron::extensions::Extensions::IMPLICIT_SOME);
+    std::fs::write(&path, ron::ser::to_string_pretty(&map, config).unwrap()).unwrap();
+}
+
+pub fn bench(paths: usize, workers: Option<usize>, flow: bool, clearance: u8) {
+    let workers = workers.unwrap_or(PathFindingSettings::default().workers);
+    let mut rand = Random::<WichmannHill>::seeded(123.456);
+
+    let mut map = GridMap(DS2Map::new(), default());
+    let mut blocks = Vec::new();
+    for _ in 0..64 {
+        let (x, y) = (rand.range_i32(-200, 200) as isize, rand.range_i32(-200, 200) as isize);
+        for x_offset in -4..=4 {
+            for y_offset in -4..=4 {
+                blocks.push((x + x_offset, y + y_offset));
+            }
+        }
+    }
+    map.0.add_objects(blocks);
+    map.0.precompute();
+
+    let mut service = PathFindingService::new(workers, map, GridSpace::new());
+    let goal = Vec2::new(rand.range(-250.0, 250.0), rand.range(-250.0, 250.0));
+    let trips: Vec<(Entity, Vec2, Vec2)> = (0..paths).map(|index| {
+        let start = Vec2::new(rand.range(-250.0, 250.0), rand.range(-250.0, 250.0));
+        let end = if flow { goal } else { Vec2::new(rand.range(-250.0, 250.0), rand.range(-250.0, 250.0)) };
+        (Entity::from_raw(index as u32), start, end)
+    }).collect();
+
+    let started = Instant::now();
+    if flow {
+        service.submit(clearance, PathRequest::Flow(goal, trips));
+    } else {
+        for (entity, start, end) in trips {
+            service.submit(clearance, PathRequest::Single(entity, start, end));
+        }
+    }
+    let (mut done, mut found) = (0, 0);
+    while done < paths {
+        for result in service.results() {
+            if service.accept(&result) {
+                done += 1;
+                if !result.path.is_empty() { found += 1; }
+            }
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    let elapsed = started.elapsed().as_secs_f32();
+    println!("{} paths ({} found) on {} workers in {:.3}s: {:.1} paths/s", paths, found, workers, elapsed, paths as f32 / elapsed);
+}
@@ -191,6 +191,9 @@ pub struct StackData {
     pub time_to_build: Duration,
     pub cost: u128,
     pub buffered: bool,
+    ///The squad a reinforcement tops up once it finishes, instead of spawning a new object.
+    #[serde(default)]
+    pub target: Option<Snowflake>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+use crate::*;
+
+///Shows what the selected structures or transports are carrying.
+#[derive(Copy, Clone)]
+#[derive(Resource)]
+pub struct CargoMenu {
+    container: Entity,
+    contents: Entity,
+}
+
+impl CargoMenu {
+    pub fn new(
+        settings: &MenuSettings,
+        font_assets: &FontAssets,
+        commands: &mut Commands,
+    ) -> Self {
+        let font = font_assets.roboto.clone();
+        let font_size = FONT_SIZE_SMALL * settings.font_size;
+
+        let container = commands.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(0.0),
+                bottom: Val::Px(240.0),
+                width: Val::Px(600.0),
+                height: Val::Px(40.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::SpaceEvenly,
+                ..default()
+            },
+            Interaction::None,
+            BackgroundColor(DARK_BACKGROUND_COLOR),
+            Visibility::Hidden,
+            BlocksRaycast,
+        )).id();
+
+        let contents = commands.spawn((
+            Text::new(""),
+            TextFont {
+                font: font.clone(),
+                font_size,
+                ..default()
+            },
+            TextColor(TEXT_COLOR_NORMAL),
+            ChildOf(container),
+        )).id();
+
+        commands.spawn((
+            Button,
+            Text::new("Unload"),
+            TextFont {
+                font: font.clone(),
+                font_size,
+                ..default()
+            },
+            TextColor(TEXT_COLOR_HOVER),
+            BlocksRaycast,
+            ChildOf(container),
+        )).observe(|
+            _trigger: Trigger<Pointer<Click>>,
+            player: Res<LocalPlayer>,
+            holders: Query<(Entity, &Selectable, &TeamPlayer, &Garrison)>,
+            mut command_events: EventWriter<CommandEvent>,
+        | {
+            let objects: Vec<Entity> = holders.iter().filter_map(|(entity, selectable, teamplayer, garrison)| (selectable.selected && *teamplayer == player.0 && !garrison.is_empty()).then_some(entity)).collect();
+            if objects.is_empty() { return; }
+            command_events.write(CommandEvent {
+                player: player.0,
+                objects,
+                command: CommandType::Ungarrison,
+            });
+        });
+
+        Self {
+            container,
+            contents,
+        }
+    }
+}
+
+pub struct CargoMenuPlugin;
+
+impl CargoMenuPlugin {
+    pub fn create_cargo_menu(
+        settings: Res<MenuSettings>,
+        font_assets: Res<FontAssets>,
+        mut commands: Commands,
+    ) {
+        let cargo_menu = CargoMenu::new(&settings, &font_assets, &mut commands);
+        commands.insert_resource(cargo_menu);
+    }
+
+    pub fn cargo_menu_update(
+        menu: Res<CargoMenu>,
+        player: Res<LocalPlayer>,
+        holders: Query<(&Selectable, &TeamPlayer, &Garrison)>,
+        object_types: Query<&ObjectType>,
+        mut texts: Query<&mut Text>,
+        mut visible_query: Query<(&mut Visibility, &InheritedVisibility)>,
+    ) {
+        let mut capacity = 0;
+        let mut occupants = Vec::new();
+        holders.iter().filter(|(selectable, teamplayer, garrison)| selectable.selected && **teamplayer == player.0 && garrison.capacity > 0).for_each(|(_, _, garrison)| {
+            capacity += garrison.capacity;
+            occupants.extend(garrison.occupants.iter().filter_map(|(_, occupant)| object_types.get((*occupant)?).ok()).map(|object_type| object_type.to_string()));
+        });
+        if capacity == 0 {
+            close(&mut visible_query, menu.container);
+            return;
+        }
+        open(&mut visible_query, menu.container);
+
+        if let Ok(mut text) = texts.get_mut(menu.contents) {
+            text.0 = format!("Cargo {}/{}", occupants.len(), capacity);
+            if !occupants.is_empty() {
+                text.0 += &format!(": {}", occupants.join(", "));
+            }
+        }
+    }
+}
+
+impl Plugin for CargoMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(GameState::SingleplayerGame), Self::create_cargo_menu)
+            .add_systems(Update, Self::cargo_menu_update.run_if(in_state(GameState::SingleplayerGame)));
+    }
+}
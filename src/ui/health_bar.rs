@@ -3,18 +3,26 @@ use crate::*;
 
 const SIZE: f32 = 16.0;
 const HEALTH_PER_SEGMENT: f32 = 200.0;
+///Roughly how wide one rank pip is, so the rank can sit just left of the bar.
+const RANK_PIP_WIDTH: f32 = SIZE * 0.375;
 
 const HEALTH_BAR_LEFT_MARGIN: f32 = 15.0;
 const HEALTH_BAR_RIGHT_MARGIN: f32 = 15.0;
 const HEALTH_BAR_TOP_MARGIN: f32 = 4.0;
 const HEALTH_BAR_BOTTOM_MARGIN: f32 = 4.0;
 
+const BURN_COLOR: Color = Color::linear_rgba(1.0, 0.35, 0.0, 1.0);
+const SHOCK_COLOR: Color = Color::linear_rgba(0.3, 0.6, 1.0, 1.0);
+
 #[derive(Debug, Clone, Copy)]
 pub struct HealthBar {
     segments: u32,
     root: Entity,
     bar: Entity,
     _decor: Entity,
+    burn: Entity,
+    shock: Entity,
+    rank: Entity,
 }
 
 impl HealthBar {
@@ -57,6 +65,7 @@ impl Component for HealthBar {
         Some(|mut world, health_bar| {
             let Some(health_bar) = world.get::<HealthBar>(health_bar.entity).cloned() else { return; };
             world.commands().entity(health_bar.root).despawn();
+            world.commands().entity(health_bar.rank).despawn();
         })
     }
 }
@@ -67,6 +76,7 @@ impl HealthBarUIPlugin {
     pub fn spawn(
         trigger: Trigger<OnAdd, Health>,
         image_assets: Res<ImageAssets>,
+        font_assets: Res<FontAssets>,
         health: Query<&Health>,
         mut commands: Commands,
     ) {
@@ -113,39 +123,89 @@ impl HealthBarUIPlugin {
             ChildOf(root),
         )).id();
 
+        let indicator = |index: f32, color: Color| (
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(SIZE * 0.125),
+                left: Val::Px(segments as f32 * SIZE + SIZE * 2.0 + 2.0 + index * SIZE * 0.75),
+                width: Val::Px(SIZE * 0.625),
+                height: Val::Px(SIZE * 0.625),
+                ..default()
+            },
+            BackgroundColor(color),
+            Visibility::Hidden,
+            ChildOf(root),
+        );
+
+        let burn = commands.spawn(indicator(0.0, BURN_COLOR)).id();
+        let shock = commands.spawn(indicator(1.0, SHOCK_COLOR)).id();
+
+        let rank = commands.spawn((
+            Text::new(""),
+            TextFont {
+                font: font_assets.roboto.clone(),
+                font_size: SIZE * 0.875,
+                ..default()
+            },
+            TextColor(TEXT_COLOR_HOVER),
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            Visibility::Hidden,
+        )).id();
+
         commands.entity(trigger.target()).insert(HealthBar {
             segments,
             root,
             bar,
             _decor: decor,
+            burn,
+            shock,
+            rank,
         });
     }
 
     pub fn update_health_bars(
         camera: Res<CameraController>,
-        health_bars: Query<(&Transform, &Health, &HealthBar)>,
+        health_bars: Query<(&Transform, &Health, &HealthBar, Option<&StatusEffects>, Option<&Veterancy>)>,
         mut nodes: Query<&mut Node>,
+        mut texts: Query<&mut Text>,
         mut visible_query: Query<(&mut Visibility, &InheritedVisibility)>,
         cameras: Query<(&Camera, &GlobalTransform)>,
     ) {
         let (camera, camera_transform) = cameras.get(camera.camera).unwrap();
-        health_bars.iter().for_each(|(tran, hel, bar)| {
-            if hel.is_full_health() {
+        health_bars.iter().for_each(|(tran, hel, bar, status_effects, veterancy)| {
+            let burning = status_effects.map_or(false, |effects| effects.is_burning());
+            let shocked = status_effects.map_or(false, |effects| effects.is_shocked());
+            //TODO: find some way to get how far up the screen to put the health bar.
+            let point = camera.world_to_viewport(camera_transform, tran.translation).ok().map(|point| point + bar.offset() - Vec2::Y * 50.0);
+
+            //The rank stays up even when the bar is hidden, so healthy veterans still show it.
+            let rank = veterancy.map_or(0, |veterancy| veterancy.rank());
+            set_visibility(&mut visible_query, bar.rank, rank > 0);
+            if rank > 0 {
+                if let Ok(mut text) = texts.get_mut(bar.rank) {
+                    let pips = "I".repeat(rank);
+                    if text.0 != pips { text.0 = pips; }
+                }
+                if let (Some(point), Ok(mut s)) = (point, nodes.get_mut(bar.rank)) {
+                    s.left = Val::Px(point.x - RANK_PIP_WIDTH * rank as f32 - 2.0);
+                    s.top = Val::Px(point.y - SIZE * 0.125);
+                }
+            }
+
+            if hel.is_full_health() && !burning && !shocked {
                 close(&mut visible_query, bar.root);
             } else {
                 open(&mut visible_query, bar.root);
-                match camera.world_to_viewport(camera_transform, tran.translation) {
-                    Ok(point) => {
-                        if let Ok(mut s) = nodes.get_mut(bar.root) {
-                            let point = point + bar.offset();
-                            s.left = Val::Px(point.x);
-                            //TODO: find some way to get how far up the screen to put the health bar.
-                            s.top = Val::Px(point.y - 50.0);
-                        }
-                    },
-                    Err(_) => { },
+                if let (Some(point), Ok(mut s)) = (point, nodes.get_mut(bar.root)) {
+                    s.left = Val::Px(point.x);
+                    s.top = Val::Px(point.y);
                 }
                 bar.adjust_bar_percent(hel.health_percent(), &mut nodes);
+                set_visibility(&mut visible_query, bar.burn, burning);
+                set_visibility(&mut visible_query, bar.shock, shocked);
             }
         });
     }
@@ -1,17 +1,21 @@
 pub mod slicers;
+pub mod cargo_menu;
 pub mod context_menu;
 pub mod main_menu;
 pub mod debug_ui;
 pub mod gameplay_ui;
 pub mod health_bar;
+pub mod stance_menu;
 pub mod ui_plugin;
 
 pub use slicers::*;
+pub use cargo_menu::*;
 pub use context_menu::*;
 pub use main_menu::*;
 pub use debug_ui::*;
 pub use gameplay_ui::*;
 pub use health_bar::*;
+pub use stance_menu::*;
 pub use ui_plugin::*;
 
 pub mod constants {
@@ -140,4 +144,4 @@ pub struct ButtonMaterials {
 }
 
 #[derive(Component)]
-pub struct InactiveButton;
\ No newline at end of file
+pub struct InactiveButton;
\ No newline at end of file
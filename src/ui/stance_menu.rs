@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+use crate::*;
+
+#[derive(Debug, Clone, Copy)]
+#[derive(Component)]
+pub struct StanceButton(pub StanceType);
+
+#[derive(Copy, Clone)]
+#[derive(Resource)]
+pub struct StanceMenu {
+    container: Entity,
+    current: Entity,
+}
+
+impl StanceMenu {
+    pub fn new(
+        settings: &MenuSettings,
+        font_assets: &FontAssets,
+        commands: &mut Commands,
+    ) -> Self {
+        let font = font_assets.roboto.clone();
+        let font_size = FONT_SIZE_SMALL * settings.font_size;
+
+        let container = commands.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(0.0),
+                bottom: Val::Px(200.0),
+                width: Val::Px(600.0),
+                height: Val::Px(40.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::SpaceEvenly,
+                ..default()
+            },
+            Interaction::None,
+            BackgroundColor(DARK_BACKGROUND_COLOR),
+            Visibility::Hidden,
+            BlocksRaycast,
+        )).id();
+
+        let current = commands.spawn((
+            Text::new(""),
+            TextFont {
+                font: font.clone(),
+                font_size,
+                ..default()
+            },
+            TextColor(TEXT_COLOR_HOVER),
+            ChildOf(container),
+        )).id();
+
+        for stance in [StanceType::Aggressive, StanceType::Defensive, StanceType::HoldPosition, StanceType::HoldFire] {
+            commands.spawn((
+                Button,
+                StanceButton(stance),
+                Text::new(stance.to_string()),
+                TextFont {
+                    font: font.clone(),
+                    font_size,
+                    ..default()
+                },
+                TextColor(TEXT_COLOR_NORMAL),
+                BlocksRaycast,
+                ChildOf(container),
+            )).observe(|
+                trigger: Trigger<Pointer<Click>>,
+                buttons: Query<&StanceButton>,
+                player: Res<LocalPlayer>,
+                units: Query<(Entity, &Selectable, &TeamPlayer), With<Stance>>,
+                mut command_events: EventWriter<CommandEvent>,
+            | {
+                let Ok(button) = buttons.get(trigger.target()) else { return; };
+                let objects: Vec<Entity> = units.iter().filter_map(|(entity, selectable, teamplayer)| (selectable.selected && *teamplayer == player.0).then_some(entity)).collect();
+                if objects.is_empty() { return; }
+                command_events.write(CommandEvent {
+                    player: player.0,
+                    objects,
+                    command: CommandType::Stance(button.0),
+                });
+            });
+        }
+
+        Self {
+            container,
+            current,
+        }
+    }
+}
+
+pub struct StanceMenuPlugin;
+
+impl StanceMenuPlugin {
+    pub fn create_stance_menu(
+        settings: Res<MenuSettings>,
+        font_assets: Res<FontAssets>,
+        mut commands: Commands,
+    ) {
+        let stance_menu = StanceMenu::new(&settings, &font_assets, &mut commands);
+        commands.insert_resource(stance_menu);
+    }
+
+    pub fn stance_menu_update(
+        menu: Res<StanceMenu>,
+        player: Res<LocalPlayer>,
+        units: Query<(&Selectable, &TeamPlayer, &Stance)>,
+        mut texts: Query<&mut Text>,
+        mut visible_query: Query<(&mut Visibility, &InheritedVisibility)>,
+    ) {
+        let mut stances = units.iter().filter(|(selectable, teamplayer, _)| selectable.selected && **teamplayer == player.0).map(|(_, _, stance)| stance.stance);
+        let Some(first) = stances.next() else {
+            close(&mut visible_query, menu.container);
+            return;
+        };
+        open(&mut visible_query, menu.container);
+
+        if let Ok(mut text) = texts.get_mut(menu.current) {
+            text.0 = if stances.all(|stance| stance == first) { first.to_string() } else { String::from("Mixed") };
+        }
+    }
+}
+
+impl Plugin for StanceMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(OnEnter(GameState::SingleplayerGame), Self::create_stance_menu)
+            .add_systems(Update, Self::stance_menu_update.run_if(in_state(GameState::SingleplayerGame)));
+    }
+}
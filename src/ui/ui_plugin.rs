@@ -6,10 +6,12 @@ pub struct ClientUIPlugins;
 impl PluginGroup for ClientUIPlugins {
     fn build(self) -> bevy::app::PluginGroupBuilder {
         PluginGroupBuilder::start::<ClientUIPlugins>()
+            .add(CargoMenuPlugin)
             .add(ContextMenuPlugin)
             .add(DebugUIPlugin)
             .add(GamePlayUIPlugin)
             .add(HealthBarUIPlugin)
             .add(MainMenuPlugin)
+            .add(StanceMenuPlugin)
     }
 }